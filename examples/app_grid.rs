@@ -1,14 +1,13 @@
 use color_eyre::eyre::Result;
 use manifest_dir_macros::directory_relative_path;
-use mint::Vector3;
 use protostar::{
-	protostar::ProtoStar,
-	xdg::{get_desktop_files, parse_desktop_file, DesktopFile},
-};
-use stardust_xr_fusion::{
-	client::{Client, FrameInfo, RootHandler},
-	spatial::Spatial,
+	application::Application,
+	config::load_blocklist,
+	layout::GridLayout,
+	protostar::{create_protostars, ProtoStar, ProtoStarConfig},
+	xdg::{discover_applications, DiscoveryConfig, TerminalApps},
 };
+use stardust_xr_fusion::client::{Client, FrameInfo, RootHandler};
 
 const APP_LIMIT: usize = 300;
 const APP_SIZE: f32 = 0.05;
@@ -34,30 +33,28 @@ async fn main() -> Result<()> {
 }
 
 struct AppGrid {
-	apps: Vec<App>,
-	//style: TextStyle,
+	apps: Vec<ProtoStar>,
 }
 impl AppGrid {
 	fn new(client: &Client) -> Self {
-		let apps = get_desktop_files()
+		let config = DiscoveryConfig::new()
+			.blocklist(load_blocklist())
+			.terminal_apps(TerminalApps::IfTerminalAvailable);
+		let applications = discover_applications(&config)
 			.into_iter()
-			.filter_map(|d| parse_desktop_file(d).ok())
-			.filter(|d| !d.no_display)
-			.enumerate()
-			.filter(|(i, _)| *i <= APP_LIMIT)
-			.filter_map(|(i, a)| {
-				App::new(
-					client.get_root(),
-					[
-						(i % 10) as f32 * (APP_SIZE + GRID_PADDING),
-						(i / 10) as f32 * (APP_SIZE + GRID_PADDING),
-						0.0,
-					],
-					a,
-					//style,
-				)
-			})
-			.collect::<Vec<_>>();
+			.take(APP_LIMIT)
+			.filter_map(|d| Application::create(d).ok())
+			.collect();
+		let layout = GridLayout {
+			columns: 10,
+			spacing: APP_SIZE + GRID_PADDING,
+		};
+		let apps = create_protostars(
+			client.get_root(),
+			applications,
+			&layout,
+			&ProtoStarConfig::default(),
+		);
 		AppGrid { apps }
 	}
 }
@@ -68,28 +65,3 @@ impl RootHandler for AppGrid {
 		}
 	}
 }
-struct App {
-	_desktop_file: DesktopFile,
-	protostar: ProtoStar,
-}
-
-impl App {
-	fn new(
-		parent: &Spatial,
-		position: impl Into<Vector3<f32>>,
-		desktop_file: DesktopFile,
-	) -> Option<Self> {
-		let position = position.into();
-		let protostar =
-			ProtoStar::create_from_desktop_file(parent, position, desktop_file.clone()).ok()?;
-		Some(App {
-			_desktop_file: desktop_file,
-			protostar,
-		})
-	}
-}
-impl RootHandler for App {
-	fn frame(&mut self, info: FrameInfo) {
-		self.protostar.frame(info);
-	}
-}
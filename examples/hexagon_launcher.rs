@@ -4,7 +4,11 @@ use manifest_dir_macros::directory_relative_path;
 use mint::Vector3;
 use protostar::{
 	application::Application,
-	xdg::{get_desktop_files, parse_desktop_file, DesktopFile, Icon, IconType},
+	config::load_blocklist,
+	xdg::{
+		discover_applications, icon_size_for_tile, DesktopFile, DiscoveryConfig, Icon, IconType,
+		TerminalApps, DEFAULT_PIXELS_PER_DEGREE, DEFAULT_VIEWING_DISTANCE,
+	},
 };
 use stardust_xr_fusion::{
 	client::{Client, FrameInfo, RootHandler},
@@ -92,13 +96,18 @@ struct AppHexGrid {
 impl AppHexGrid {
 	fn new(client: &Client) -> Self {
 		let button = Button::new(client).unwrap();
-		let mut desktop_files: Vec<DesktopFile> = get_desktop_files()
-			.into_iter()
-			.filter_map(|d| parse_desktop_file(d).ok())
-			.filter(|d| !d.no_display)
-			.collect();
+		let mut desktop_files: Vec<DesktopFile> = discover_applications(
+			&DiscoveryConfig::new()
+				.blocklist(load_blocklist())
+				.terminal_apps(TerminalApps::IfTerminalAvailable),
+		);
 
 		desktop_files.sort_by_key(|d| d.clone().name.unwrap_or_default());
+		let icon_size = icon_size_for_tile(
+			APP_SIZE,
+			DEFAULT_VIEWING_DISTANCE,
+			DEFAULT_PIXELS_PER_DEGREE,
+		);
 
 		let mut apps = Vec::new();
 		let mut radius = 1;
@@ -117,6 +126,7 @@ impl AppHexGrid {
 							button.grabbable.content_parent(),
 							hex.get_coords(),
 							desktop_files.pop().unwrap(),
+							icon_size,
 						)
 						.unwrap(),
 					);
@@ -266,11 +276,12 @@ impl App {
 		parent: &Spatial,
 		position: impl Into<Vector3<f32>>,
 		desktop_file: DesktopFile,
+		icon_size: u16,
 	) -> Result<Self> {
 		let position = position.into();
 		let field = BoxField::create(parent, Transform::default(), [APP_SIZE; 3])?;
-		let application = Application::create(&parent.client()?, desktop_file)?;
-		let icon = application.icon(128, false);
+		let application = Application::create(desktop_file)?;
+		let icon = application.icon(icon_size, false);
 		let grabbable = Grabbable::create(
 			parent,
 			Transform::from_position(position),
@@ -261,7 +261,7 @@ impl App {
 	) -> Result<Self> {
 		let position = position.into();
 		let field = BoxField::create(parent, Transform::default(), [APP_SIZE; 3])?;
-		let application = Application::create(&parent.client()?, desktop_file)?;
+		let application = Application::create(desktop_file)?;
 		let icon = application.icon(128, false);
 		let grabbable = Grabbable::create(
 			parent,
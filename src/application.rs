@@ -1,45 +1,404 @@
-use crate::xdg::{DesktopFile, Icon, IconType};
-use nix::unistd::setsid;
-use regex::Regex;
+use crate::{
+	atlas::{build_atlas, Atlas},
+	category::{main_category, CategoryMatching},
+	color::Rgba,
+	exec::{
+		expand_env_refs, needs_shell, resolve_program, substitute_field_codes,
+		substitute_field_codes_for_shell, tokenize_exec,
+	},
+	pool::ICON_POOL,
+	xdg::{
+		discover_applications, validate_desktop_file, DesktopAction, DesktopFile,
+		DesktopFileWarning, DiscoveryConfig, Icon, IconError, IconResolver, IconType,
+	},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use nix::{
+	libc,
+	sys::stat::{umask, Mode},
+	unistd::setsid,
+};
+use resvg::{
+	tiny_skia::{Pixmap, Transform},
+	usvg::{FitTo, Options, Tree},
+};
 use stardust_xr_fusion::{
-	client::Client,
 	node::{NodeError, NodeType},
 	spatial::Spatial,
 	startup_settings::StartupSettings,
 };
 use std::{
+	collections::{HashMap, HashSet},
+	fmt::{self, Debug, Display},
+	future::Future,
+	io::ErrorKind,
 	os::unix::process::CommandExt,
+	path::{Path, PathBuf},
 	process::{Command, Stdio},
-	sync::Arc,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	time::{Duration, SystemTime},
 };
+use tokio::sync::oneshot;
+use tracing::error;
+use usvg_text_layout::{fontdb, TreeTextToPath};
+
+/// Time between each spawn in `launch_many`
+const LAUNCH_STAGGER: Duration = Duration::from_millis(100);
+/// IDs or names of desktop actions that open the settings of their app, lowercase
+const PREFERENCES_ACTION_NAMES: [&str; 6] = [
+	"preferences",
+	"settings",
+	"configure",
+	"options",
+	"prefs",
+	"open-preferences",
+];
+/// Set on launched applications to the ID of the desktop file they were launched from
+pub const LAUNCHED_ID_ENV: &str = "PROTOSTAR_LAUNCHED_ID";
+/// Set on launched applications to the `StartupWMClass` of their desktop file, when it has one,
+/// so their windows can be matched back to the tile
+pub const LAUNCHED_WM_CLASS_ENV: &str = "PROTOSTAR_LAUNCHED_WM_CLASS";
+
+#[derive(Debug)]
+pub enum LaunchError {
+	/// The server couldn't give a startup token, so the window can't be placed in the launch space
+	StartupToken(NodeError),
+	/// The server couldn't say how to connect to it
+	ConnectionEnvironment(NodeError),
+	Spawn(std::io::Error),
+}
+impl Display for LaunchError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LaunchError::StartupToken(err) => write!(f, "failed to get a startup token: {:?}", err),
+			LaunchError::ConnectionEnvironment(err) => {
+				write!(f, "failed to get the connection environment: {:?}", err)
+			}
+			LaunchError::Spawn(err) => write!(f, "failed to start the process: {}", err),
+		}
+	}
+}
+impl std::error::Error for LaunchError {}
+
+pub type LaunchErrorHandler = Arc<dyn Fn(&Application, &LaunchError) + Send + Sync>;
+/// Rewrites the full argv of a launch right before it's spawned, see `LaunchOptions::transform_argv`
+pub type ArgvTransformer = Arc<dyn Fn(Vec<String>) -> Vec<String> + Send + Sync>;
+
+/// Runs launched applications through `wrapper`, eg: `["systemd-run", "--user", "--scope"]`
+pub fn wrap_with(wrapper: Vec<String>) -> ArgvTransformer {
+	Arc::new(move |argv| wrapper.iter().cloned().chain(argv).collect())
+}
+
+/// What an application mainly opens, from its MIME types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+	Audio,
+	Video,
+	Image,
+	Text,
+}
+impl MediaKind {
+	const ALL: [MediaKind; 4] = [
+		MediaKind::Audio,
+		MediaKind::Video,
+		MediaKind::Image,
+		MediaKind::Text,
+	];
+	fn top_level_type(self) -> &'static str {
+		match self {
+			MediaKind::Audio => "audio",
+			MediaKind::Video => "video",
+			MediaKind::Image => "image",
+			MediaKind::Text => "text",
+		}
+	}
+	/// Generic icon of the kind from the icon naming spec, eg: for apps without an icon
+	pub fn icon_name(self) -> &'static str {
+		match self {
+			MediaKind::Audio => "audio-x-generic",
+			MediaKind::Video => "video-x-generic",
+			MediaKind::Image => "image-x-generic",
+			MediaKind::Text => "text-x-generic",
+		}
+	}
+}
+
+/// Tweaks to a single launch, on top of what the desktop file says
+#[derive(Clone, Default)]
+pub struct LaunchOptions {
+	/// Appended to the arguments of the `Exec` key
+	pub extra_args: Vec<String>,
+	/// Paths or URLs to open, they fill in the `%f`, `%F`, `%u` and `%U` field codes of the `Exec` key
+	pub files: Vec<String>,
+	/// Still launch when there's no startup token, the window just won't be placed in the launch space
+	pub fallback_without_token: bool,
+	/// Called when the launch fails after `launch_with_options` returned, failures are logged either way
+	pub on_error: Option<LaunchErrorHandler>,
+	/// Niceness the application runs with, from -20 to 19, eg: to keep it from starving the compositor
+	pub niceness: Option<i32>,
+	/// File mode creation mask the application runs with, eg: `0o077`
+	pub umask: Option<u32>,
+	/// Run the `Exec` arguments directly instead of through `sh -c`. Only the references to the
+	/// variables in `EXPANDABLE_ENV_VARS` get expanded then, nothing else a shell would do happens.
+	/// Entries that rely on the shell (pipes, `&&`, redirections... see `needs_shell`) still go through it.
+	pub without_shell: bool,
+	/// Command to run applications that prefer the discrete GPU (`PrefersNonDefaultGPU`) through,
+	/// eg: `["prime-run"]`
+	pub discrete_gpu_wrapper: Option<Vec<String>>,
+	/// Set on every launched application, eg: the ones `scaling_env` gives
+	pub scaling_env: Vec<(String, String)>,
+	/// Don't pass the launcher's own `DISPLAY_ENV_VARS` on, so applications only ever
+	/// connect to the displays the connection environment of the server gives
+	pub sanitize_display: bool,
+	/// Applied to the argv after every other option (wrappers, extra arguments...), eg: `wrap_with`
+	/// to run applications in a sandbox. An empty argv cancels the launch.
+	pub transform_argv: Option<ArgvTransformer>,
+	/// Where applications whose desktop file has no `Path` key start, eg: `$HOME`, rather than
+	/// wherever the launcher itself happens to run from
+	pub default_working_dir: Option<PathBuf>,
+}
+impl Debug for LaunchOptions {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LaunchOptions")
+			.field("extra_args", &self.extra_args)
+			.field("files", &self.files)
+			.field("fallback_without_token", &self.fallback_without_token)
+			.field("on_error", &self.on_error.is_some())
+			.field("niceness", &self.niceness)
+			.field("umask", &self.umask)
+			.field("without_shell", &self.without_shell)
+			.field("discrete_gpu_wrapper", &self.discrete_gpu_wrapper)
+			.field("scaling_env", &self.scaling_env)
+			.field("sanitize_display", &self.sanitize_display)
+			.field("transform_argv", &self.transform_argv.is_some())
+			.field("default_working_dir", &self.default_working_dir)
+			.finish()
+	}
+}
+
+/// Variables telling applications which display server to connect to
+pub const DISPLAY_ENV_VARS: [&str; 2] = ["DISPLAY", "WAYLAND_DISPLAY"];
+
+/// Variables that make GTK, Qt and EFL applications render at `scale`, GTK only takes whole numbers
+pub fn scaling_env(scale: f32) -> Vec<(String, String)> {
+	let gdk_scale = scale.round().max(1.0);
+	[
+		("GDK_SCALE", gdk_scale.to_string()),
+		("GDK_DPI_SCALE", (scale / gdk_scale).to_string()),
+		("QT_SCALE_FACTOR", scale.to_string()),
+		("ELM_SCALE", scale.to_string()),
+	]
+	.into_iter()
+	.map(|(var, value)| (var.to_string(), value))
+	.collect()
+}
+
+/// Waits for what the server has to provide for a launch
+async fn launch_environment<E>(
+	startup_token: impl Future<Output = Result<String, NodeError>>,
+	connection_env: impl Future<Output = Result<E, NodeError>>,
+	fallback_without_token: bool,
+) -> Result<(Option<String>, E), LaunchError> {
+	let startup_token = match startup_token.await {
+		Ok(startup_token) => Some(startup_token),
+		Err(err) if fallback_without_token => {
+			error!("{}, launching without it", LaunchError::StartupToken(err));
+			None
+		}
+		Err(err) => return Err(LaunchError::StartupToken(err)),
+	};
+	let connection_env = connection_env
+		.await
+		.map_err(LaunchError::ConnectionEnvironment)?;
+	Ok((startup_token, connection_env))
+}
+
+#[test]
+fn test_launch_environment_token_failure() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let failing_token = || async { Err(NodeError::DoesNotExist) };
+	let env = || async { Ok(vec![("FLAT_CLIENT".to_string(), "1".to_string())]) };
+
+	let result = runtime.block_on(launch_environment(failing_token(), env(), false));
+	assert!(matches!(result, Err(LaunchError::StartupToken(_))));
+
+	let (startup_token, connection_env) = runtime
+		.block_on(launch_environment(failing_token(), env(), true))
+		.unwrap();
+	assert_eq!(startup_token, None);
+	assert_eq!(connection_env.len(), 1);
+}
 
 #[derive(Debug, Clone)]
 pub struct Application {
 	desktop_file: DesktopFile,
-	startup_settings: Arc<StartupSettings>,
 }
 impl Application {
-	pub fn create(client: &Arc<Client>, desktop_file: DesktopFile) -> Result<Self, NodeError> {
+	pub fn create(desktop_file: DesktopFile) -> Result<Self, NodeError> {
 		if desktop_file.no_display {
 			return Err(NodeError::DoesNotExist);
 		}
 
-		let startup_settings = Arc::new(StartupSettings::create(client)?);
-		Ok(Application {
-			desktop_file,
-			startup_settings,
-		})
+		Ok(Application { desktop_file })
 	}
 
+	/// The desktop file ID, eg: `firefox.desktop`
+	pub fn id(&self) -> String {
+		self.desktop_file.id()
+	}
 	pub fn name(&self) -> Option<&str> {
 		self.desktop_file.name.as_deref()
 	}
+	pub fn generic_name(&self) -> Option<&str> {
+		self.desktop_file.generic_name.as_deref()
+	}
+	pub fn keywords(&self) -> &[String] {
+		self.desktop_file.keywords.as_slice()
+	}
+	/// The name in `locale`, or in the environment's locale if it's `None`
+	pub fn localized_name(&self, locale: Option<&str>) -> Option<&str> {
+		self.desktop_file.localized_name(locale)
+	}
+	/// The name to show, the vendor full name (eg: `X-GNOME-FullName`) if `prefer_full_name` and there's one
+	pub fn display_name(&self, locale: Option<&str>, prefer_full_name: bool) -> Option<&str> {
+		prefer_full_name
+			.then(|| self.desktop_file.localized_full_name(locale))
+			.flatten()
+			.or_else(|| self.localized_name(locale))
+	}
+	/// Name, generic name and comment in one sentence for screen readers,
+	/// eg: `Firefox, Web Browser. Browse the Web`
+	pub fn accessible_description(&self, locale: Option<&str>) -> String {
+		let name = self
+			.localized_name(locale)
+			.map(str::to_string)
+			.unwrap_or_else(|| self.id());
+		let mut description = name.clone();
+		if let Some(generic_name) = self.desktop_file.localized_generic_name(locale) {
+			if generic_name != name {
+				description.push_str(", ");
+				description.push_str(generic_name);
+			}
+		}
+		if let Some(comment) = self.desktop_file.localized_comment(locale) {
+			description.push_str(". ");
+			description.push_str(comment);
+		}
+		description
+	}
+	/// The value of the `Icon` key, an icon name or a path
+	pub fn icon_name(&self) -> Option<&str> {
+		self.desktop_file.icon.as_deref()
+	}
 	pub fn categories(&self) -> &[String] {
 		self.desktop_file.categories.as_slice()
 	}
+	pub fn actions(&self) -> &[DesktopAction] {
+		self.desktop_file.actions.as_slice()
+	}
+	/// The desktop action that opens the settings of the app, going by the conventional IDs and names of such actions
+	pub fn preferences_action(&self) -> Option<&DesktopAction> {
+		let is_preferences = |text: &str| {
+			let text = text.to_lowercase();
+			PREFERENCES_ACTION_NAMES.contains(&text.as_str())
+		};
+		let actions = self.actions();
+		actions
+			.iter()
+			.find(|action| is_preferences(&action.id))
+			.or_else(|| {
+				actions
+					.iter()
+					.find(|action| action.name.as_deref().is_some_and(is_preferences))
+			})
+	}
+	pub fn mime_types(&self) -> &[String] {
+		self.desktop_file.mime_types.as_slice()
+	}
+	/// The kind of media most of the declared MIME types are, if any
+	pub fn media_kind(&self) -> Option<MediaKind> {
+		let mut counts = [0; MediaKind::ALL.len()];
+		for mime_type in self.mime_types() {
+			let top_level = mime_type.split('/').next().unwrap_or_default();
+			if let Some(i) = MediaKind::ALL
+				.iter()
+				.position(|kind| kind.top_level_type() == top_level)
+			{
+				counts[i] += 1;
+			}
+		}
+		// On a tie the kind listed first in `ALL` wins
+		let (i, count) = counts
+			.iter()
+			.enumerate()
+			.rev()
+			.max_by_key(|(_, count)| **count)?;
+		(*count > 0).then(|| MediaKind::ALL[i])
+	}
+	pub fn single_main_window(&self) -> bool {
+		self.desktop_file.single_main_window
+	}
+	pub fn startup_wm_class(&self) -> Option<&str> {
+		self.desktop_file.startup_wm_class.as_deref()
+	}
+	pub fn modified(&self) -> Option<SystemTime> {
+		self.desktop_file.modified()
+	}
+
+	/// A color unique to this application, to tell tiles apart without relying on categories
+	pub fn hashed_color(&self) -> Rgba {
+		Rgba::hashed(&self.id())
+	}
+
+	/// The program the `Exec` key runs, as written in it
+	fn program(&self) -> Option<String> {
+		let exec = self.desktop_file.command.as_ref()?;
+		tokenize_exec(exec).into_iter().next()
+	}
+
+	/// Absolute path of the program the `Exec` key runs, looked up in `$PATH` if needed.
+	/// `None` if it doesn't exist or isn't executable.
+	pub fn resolved_executable(&self) -> Option<PathBuf> {
+		resolve_program(&self.program()?)
+	}
+
+	/// Whether there's something to run, checked with `TryExec` if the entry has one
+	pub fn is_runnable(&self) -> bool {
+		match &self.desktop_file.try_exec {
+			Some(try_exec) => resolve_program(try_exec).is_some(),
+			None => self.resolved_executable().is_some(),
+		}
+	}
 
 	pub fn icon(&self, preferred_px_size: u16, prefer_3d: bool) -> Option<Icon> {
-		let raw_icons = self.desktop_file.get_raw_icons(preferred_px_size);
+		self.try_icon(preferred_px_size, prefer_3d).ok()
+	}
+	/// Like `icon`, but tells an application without an icon apart from one whose icon is broken
+	pub fn try_icon(&self, preferred_px_size: u16, prefer_3d: bool) -> Result<Icon, IconError> {
+		self.try_icon_with(&IconResolver::default(), preferred_px_size, prefer_3d)
+	}
+	/// Like `try_icon`, resolved the way `resolver` is set up to
+	pub fn try_icon_with(
+		&self,
+		resolver: &IconResolver,
+		preferred_px_size: u16,
+		prefer_3d: bool,
+	) -> Result<Icon, IconError> {
+		if self.desktop_file.icon.is_none() {
+			if resolver.uses_category_icon_fallback() {
+				let category = main_category(self, CategoryMatching::Exact);
+				return resolver
+					.icon_for_category(&category, preferred_px_size)
+					.ok_or(IconError::NotDeclared);
+			}
+			return Err(IconError::NotDeclared);
+		}
+		let raw_icons = self
+			.desktop_file
+			.get_raw_icons_with(resolver, preferred_px_size);
 		let mut icon = raw_icons.iter().max_by_key(|i| i.size).cloned();
 		if prefer_3d {
 			icon = raw_icons
@@ -51,50 +410,1776 @@ impl Application {
 				.or(icon);
 		}
 
-		icon.and_then(|i| i.cached_process(preferred_px_size).ok())
+		icon.ok_or(IconError::NotFound)?
+			.cached_process_with(resolver, preferred_px_size)
+			.map_err(IconError::RenderFailed)
+	}
+	/// Like `icon`, but gives up after `timeout` (eg: on slow network storage) so the fallback icon can be used instead.
+	/// The lookup keeps going in the background and still fills the cache once it finishes.
+	pub fn icon_timeout(
+		&self,
+		preferred_px_size: u16,
+		prefer_3d: bool,
+		timeout: Duration,
+	) -> Option<Icon> {
+		let app = self.clone();
+		with_timeout(timeout, move || app.icon(preferred_px_size, prefer_3d))
+	}
+	/// Like `icon`, but only ever a PNG or a glTF model, the formats the server can load
+	pub fn backend_ready_icon(&self, preferred_px_size: u16, prefer_3d: bool) -> Option<Icon> {
+		self.try_backend_ready_icon(preferred_px_size, prefer_3d)
+			.ok()
+	}
+	pub fn try_backend_ready_icon(
+		&self,
+		preferred_px_size: u16,
+		prefer_3d: bool,
+	) -> Result<Icon, IconError> {
+		self.try_backend_ready_icon_with(&IconResolver::default(), preferred_px_size, prefer_3d)
+	}
+	pub fn try_backend_ready_icon_with(
+		&self,
+		resolver: &IconResolver,
+		preferred_px_size: u16,
+		prefer_3d: bool,
+	) -> Result<Icon, IconError> {
+		let icon = self.try_icon_with(resolver, preferred_px_size, prefer_3d)?;
+		match icon.icon_type {
+			IconType::Png | IconType::Gltf => Ok(icon),
+			_ => Err(IconError::RenderFailed(ErrorKind::Unsupported.into())),
+		}
+	}
+	/// Disk space the cached renders of the icons of the application take, in bytes
+	pub fn icon_cache_size(&self) -> u64 {
+		IconResolver::default().app_cache_size(self)
+	}
+	pub fn has_icon(&self, preferred_px_size: u16) -> bool {
+		self.icon(preferred_px_size, false).is_some()
 	}
 
+	/// Launches the application, its startup settings are rooted to `launch_space` so its first
+	/// window gets parented to it (eg: a workspace instead of the tile it was launched from)
 	pub fn launch(&self, launch_space: &Spatial) -> Result<(), NodeError> {
-		self.startup_settings.set_root(launch_space)?;
-		let future_startup_token = self.startup_settings.generate_startup_token()?;
-		let future_connection_env = self
-			.startup_settings
-			.node()
-			.client()?
-			.get_connection_environment()?;
-
-		let executable = self
+		self.launch_with_callback(launch_space, |_| ())
+	}
+
+	/// Same as `launch`, with ad-hoc arguments added after the ones of the `Exec` key
+	pub fn launch_with_extra_args(
+		&self,
+		extra_args: &[String],
+		launch_space: &Spatial,
+	) -> Result<(), NodeError> {
+		let options = LaunchOptions {
+			extra_args: extra_args.to_vec(),
+			..Default::default()
+		};
+		self.launch_with_options(launch_space, options, |_| ())
+	}
+
+	/// Same as `launch`, but `on_launch` gets called right before the application is spawned
+	/// so a launcher can show it's starting up immediately
+	pub fn launch_with_callback(
+		&self,
+		launch_space: &Spatial,
+		on_launch: impl FnOnce(&Application),
+	) -> Result<(), NodeError> {
+		self.launch_with_options(launch_space, LaunchOptions::default(), on_launch)
+	}
+
+	/// Launches with `options`, `on_launch` gets called right before the application is spawned
+	pub fn launch_with_options(
+		&self,
+		launch_space: &Spatial,
+		options: LaunchOptions,
+		on_launch: impl FnOnce(&Application),
+	) -> Result<(), NodeError> {
+		self.spawn_launch(launch_space, options, on_launch, None)
+	}
+
+	/// Same as `launch_with_options`, the returned receiver gets the PID of the spawned process as
+	/// soon as it's spawned, or gets closed if the launch fails
+	pub fn launch_with_pid(
+		&self,
+		launch_space: &Spatial,
+		options: LaunchOptions,
+	) -> Result<oneshot::Receiver<u32>, NodeError> {
+		let (pid_sender, pid) = oneshot::channel();
+		self.spawn_launch(launch_space, options, |_| (), Some(pid_sender))?;
+		Ok(pid)
+	}
+
+	fn spawn_launch(
+		&self,
+		launch_space: &Spatial,
+		options: LaunchOptions,
+		on_launch: impl FnOnce(&Application),
+		pid_sender: Option<oneshot::Sender<u32>>,
+	) -> Result<(), NodeError> {
+		// Every launch gets its own startup settings, and so its own startup token
+		let client = launch_space.client()?;
+		let startup_settings = StartupSettings::create(&client)?;
+		startup_settings.set_root(launch_space)?;
+		let future_startup_token = startup_settings.generate_startup_token()?;
+		let future_connection_env = client.get_connection_environment()?;
+
+		self.start_launch(on_launch, |application| {
+			tokio::task::spawn(async move {
+				// The startup settings only need to live until the token is generated
+				let future_startup_token = async move {
+					let startup_token = future_startup_token.await;
+					drop(startup_settings);
+					startup_token
+				};
+				let result = launch_environment(
+					future_startup_token,
+					future_connection_env,
+					options.fallback_without_token,
+				)
+				.await
+				.and_then(|(startup_token, connection_env)| {
+					let Some(command) = application.launch_command(&options, connection_env, startup_token.as_deref()) else {return Ok(())};
+					spawn_command(command, pid_sender)
+				});
+				if let Err(err) = result {
+					error!("Failed to launch {}: {}", application.id(), err);
+					if let Some(on_error) = &options.on_error {
+						on_error(&application, &err);
+					}
+				}
+			});
+		})
+	}
+
+	/// Calls `on_launch` and then `spawn`, unless the application has nothing to run
+	fn start_launch(
+		&self,
+		on_launch: impl FnOnce(&Application),
+		spawn: impl FnOnce(Application),
+	) -> Result<(), NodeError> {
+		if self.desktop_file.command.is_none() {
+			return Err(NodeError::DoesNotExist);
+		}
+		on_launch(self);
+		spawn(self.clone());
+		Ok(())
+	}
+
+	/// The command to spawn the application with, the environment is only set on the child
+	fn launch_command(
+		&self,
+		options: &LaunchOptions,
+		connection_env: impl IntoIterator<Item = (String, String)>,
+		startup_token: Option<&str>,
+	) -> Option<Command> {
+		let executable = self.desktop_file.command.as_ref()?;
+		let mut args: Vec<String> = Vec::new();
+		if self.desktop_file.prefers_non_default_gpu {
+			if let Some(wrapper) = &options.discrete_gpu_wrapper {
+				args.extend(wrapper.iter().cloned());
+			}
+		}
+		if options.without_shell && !needs_shell(executable) {
+			// Variables are expanded first so the files are passed on untouched
+			args.extend(
+				tokenize_exec(executable)
+					.iter()
+					.map(|arg| expand_env_refs(arg))
+					.flat_map(|arg| substitute_field_codes(&arg, &options.files)),
+			);
+		} else {
+			let mut exec = substitute_field_codes_for_shell(executable, &options.files);
+			if !options.extra_args.is_empty() {
+				// Passed as positional parameters so they don't need to be quoted for the shell
+				exec.push_str(" \"$@\"");
+			}
+			args.extend(["sh".to_string(), "-c".to_string(), exec, "sh".to_string()]);
+		}
+		args.extend(options.extra_args.iter().cloned());
+		if let Some(transform_argv) = &options.transform_argv {
+			args = transform_argv(args);
+		}
+		let (program, args) = args.split_first()?;
+
+		let mut command = Command::new(program);
+		if options.sanitize_display {
+			for var in DISPLAY_ENV_VARS {
+				command.env_remove(var);
+			}
+		}
+		command
+			.args(args)
+			.envs(options.scaling_env.iter().cloned())
+			.envs(connection_env)
+			.stdin(Stdio::null())
+			.stdout(Stdio::null())
+			.stderr(Stdio::null());
+		if let Some(startup_token) = startup_token {
+			command.env("STARDUST_STARTUP_TOKEN", startup_token);
+		}
+		let working_dir = self
+			.desktop_file
+			.extra
+			.get("Path")
+			.filter(|path| !path.is_empty())
+			.map(PathBuf::from)
+			.or_else(|| options.default_working_dir.clone());
+		if let Some(working_dir) = working_dir {
+			command.current_dir(working_dir);
+		}
+		let id = self.id();
+		if !id.is_empty() {
+			command.env(LAUNCHED_ID_ENV, id);
+		}
+		if let Some(wm_class) = &self.desktop_file.startup_wm_class {
+			command.env(LAUNCHED_WM_CLASS_ENV, wm_class);
+		}
+		let (niceness, file_mask) = (options.niceness, options.umask);
+		unsafe {
+			command.pre_exec(move || {
+				_ = setsid();
+				if let Some(niceness) = niceness {
+					if libc::setpriority(libc::PRIO_PROCESS, 0, niceness) != 0 {
+						return Err(std::io::Error::last_os_error());
+					}
+				}
+				if let Some(file_mask) = file_mask {
+					umask(Mode::from_bits_truncate(file_mask));
+				}
+				Ok(())
+			});
+		}
+		Some(command)
+	}
+}
+
+#[test]
+fn test_launch_callback_order() {
+	let application = Application::create(DesktopFile {
+		command: Some("app".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let events = std::cell::RefCell::new(Vec::new());
+	application
+		.start_launch(
+			|_| events.borrow_mut().push("callback"),
+			|_| events.borrow_mut().push("spawn"),
+		)
+		.unwrap();
+	assert_eq!(*events.borrow(), ["callback", "spawn"]);
+
+	// Nothing to run, so neither gets called
+	let commandless = Application::create(DesktopFile::default()).unwrap();
+	events.borrow_mut().clear();
+	assert!(commandless
+		.start_launch(
+			|_| events.borrow_mut().push("callback"),
+			|_| events.borrow_mut().push("spawn"),
+		)
+		.is_err());
+	assert!(events.borrow().is_empty());
+}
+
+/// Runs `f` on the icon workers, `None` if it takes longer than `timeout`.
+/// A lookup that hangs keeps holding its worker, so they can't pile up past `ICON_WORKERS`
+fn with_timeout<T: Send + 'static>(
+	timeout: Duration,
+	f: impl FnOnce() -> Option<T> + Send + 'static,
+) -> Option<T> {
+	let (sender, receiver) = std::sync::mpsc::channel();
+	ICON_POOL.execute(move || {
+		let _ = sender.send(f());
+	});
+	receiver.recv_timeout(timeout).ok().flatten()
+}
+
+#[test]
+fn test_with_timeout() {
+	let start = std::time::Instant::now();
+	let slow = with_timeout(Duration::from_millis(50), || {
+		std::thread::sleep(Duration::from_secs(2));
+		Some(1)
+	});
+	assert_eq!(slow, None);
+	assert!(start.elapsed() < Duration::from_secs(1));
+
+	// Timed out lookups don't each get a thread of their own
+	use crate::pool::ICON_WORKERS;
+	use std::sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	};
+	let running = Arc::new(AtomicUsize::new(0));
+	let most_running = Arc::new(AtomicUsize::new(0));
+	let finished = Arc::new(AtomicUsize::new(0));
+	for _ in 0..ICON_WORKERS * 2 {
+		let (running, most_running, finished) =
+			(running.clone(), most_running.clone(), finished.clone());
+		let result = with_timeout(Duration::from_millis(1), move || {
+			let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+			most_running.fetch_max(now, Ordering::SeqCst);
+			std::thread::sleep(Duration::from_millis(100));
+			running.fetch_sub(1, Ordering::SeqCst);
+			finished.fetch_add(1, Ordering::SeqCst);
+			Some(())
+		});
+		assert_eq!(result, None);
+	}
+	let deadline = std::time::Instant::now() + Duration::from_secs(10);
+	while finished.load(Ordering::SeqCst) < ICON_WORKERS * 2 {
+		assert!(std::time::Instant::now() < deadline);
+		std::thread::sleep(Duration::from_millis(10));
+	}
+	assert!((1..=ICON_WORKERS).contains(&most_running.load(Ordering::SeqCst)));
+
+	assert_eq!(with_timeout(Duration::from_secs(5), || Some(2)), Some(2));
+	assert_eq!(with_timeout(Duration::from_secs(5), || None::<u8>), None);
+
+	let iconless = Application::create(DesktopFile {
+		command: Some("app".into()),
+		..Default::default()
+	})
+	.unwrap();
+	assert_eq!(
+		iconless.icon_timeout(64, false, Duration::from_secs(5)),
+		None
+	);
+}
+
+/// Spawns `command` without waiting for it, `pid_sender` gets the PID of the child
+fn spawn_command(
+	mut command: Command,
+	pid_sender: Option<oneshot::Sender<u32>>,
+) -> Result<(), LaunchError> {
+	let child = command.spawn().map_err(LaunchError::Spawn)?;
+	if let Some(pid_sender) = pid_sender {
+		let _ = pid_sender.send(child.id());
+	}
+	Ok(())
+}
+
+#[test]
+fn test_spawn_command_pid() {
+	use nix::{
+		sys::signal::{kill, Signal},
+		unistd::Pid,
+	};
+	let _env = crate::test_utils::lock_env();
+	let application = Application::create(DesktopFile {
+		command: Some("sleep 5".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let command = application
+		.launch_command(&LaunchOptions::default(), [], None)
+		.unwrap();
+	let (pid_sender, mut pid) = oneshot::channel();
+	spawn_command(command, Some(pid_sender)).unwrap();
+
+	let pid = pid.try_recv().unwrap();
+	assert!(pid > 0);
+	assert!(Path::new(&format!("/proc/{}", pid)).exists());
+	kill(Pid::from_raw(pid as i32), Signal::SIGKILL).unwrap();
+
+	// Failed spawns close the channel
+	let (pid_sender, mut pid) = oneshot::channel();
+	assert!(spawn_command(Command::new("surely-not-a-real-program"), Some(pid_sender)).is_err());
+	assert!(pid.try_recv().is_err());
+}
+
+/// Launches all the `apps` into `launch_space`, staggered so they don't all spawn at once
+pub async fn launch_many(
+	apps: &[Application],
+	launch_space: &Spatial,
+) -> Vec<Result<(), NodeError>> {
+	launch_staggered(apps, |app| app.launch(launch_space)).await
+}
+async fn launch_staggered(
+	apps: &[Application],
+	mut launch: impl FnMut(&Application) -> Result<(), NodeError>,
+) -> Vec<Result<(), NodeError>> {
+	let mut results = Vec::with_capacity(apps.len());
+	for (i, app) in apps.iter().enumerate() {
+		if i > 0 {
+			tokio::time::sleep(LAUNCH_STAGGER).await;
+		}
+		results.push(launch(app));
+	}
+	results
+}
+
+#[test]
+fn test_launch_many() {
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.enable_time()
+		.start_paused(true)
+		.build()
+		.unwrap();
+	let app = |id: &str, command: Option<&str>| {
+		Application::create(DesktopFile {
+			path: PathBuf::from(format!("/usr/share/applications/{}.desktop", id)),
+			command: command.map(str::to_string),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = [
+		app("browser", Some("browser")),
+		app("broken", None),
+		app("editor", Some("editor")),
+	];
+
+	let mut launched = Vec::new();
+	let results = runtime.block_on(launch_staggered(&apps, |app| {
+		launched.push(app.id());
+		match app.desktop_file.command {
+			Some(_) => Ok(()),
+			None => Err(NodeError::DoesNotExist),
+		}
+	}));
+	assert_eq!(
+		launched,
+		["browser.desktop", "broken.desktop", "editor.desktop"]
+	);
+	assert_eq!(results.len(), apps.len());
+	assert!(results[0].is_ok());
+	assert!(matches!(results[1], Err(NodeError::DoesNotExist)));
+	assert!(results[2].is_ok());
+}
+
+/// Stops a `preprocess_icons` batch from another thread, eg: when the launcher refreshes
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+impl CancelToken {
+	pub fn new() -> Self {
+		CancelToken::default()
+	}
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+/// Resolves and renders the icons of `apps` ahead of time so they're already cached when tiles get
+/// created. Stops before the next icon once `cancel` is cancelled, returns how many were processed.
+pub fn preprocess_icons<'a>(
+	apps: impl IntoIterator<Item = &'a Application>,
+	preferred_px_size: u16,
+	cancel: &CancelToken,
+) -> usize {
+	let mut processed = 0;
+	for app in apps {
+		if cancel.is_cancelled() {
+			break;
+		}
+		let _ = app.icon(preferred_px_size, false);
+		processed += 1;
+	}
+	processed
+}
+
+#[test]
+fn test_preprocess_icons_cancel() {
+	let apps: Vec<_> = (0..3)
+		.map(|i| {
+			Application::create(DesktopFile {
+				name: Some(format!("App {}", i)),
+				..Default::default()
+			})
+			.unwrap()
+		})
+		.collect();
+
+	assert_eq!(preprocess_icons(&apps, 64, &CancelToken::new()), 3);
+
+	// Cancelled while the second icon is up next
+	let cancel = CancelToken::new();
+	let processed = preprocess_icons(
+		apps.iter().enumerate().map(|(i, app)| {
+			if i == 1 {
+				cancel.cancel();
+			}
+			app
+		}),
+		64,
+		&cancel,
+	);
+	assert_eq!(processed, 1);
+}
+
+/// `apps` in the order their icons should be warmed: the ones `is_visible` (eg: on the current or
+/// next page, by index) first, then the rest, both keeping their order
+pub fn icon_warming_order<'a>(
+	apps: &'a [Application],
+	is_visible: impl Fn(usize, &Application) -> bool,
+) -> Vec<&'a Application> {
+	let (mut visible, hidden): (Vec<_>, Vec<_>) = apps
+		.iter()
+		.enumerate()
+		.partition(|(i, app)| is_visible(*i, app));
+	visible.extend(hidden);
+	visible.into_iter().map(|(_, app)| app).collect()
+}
+
+/// Like `preprocess_icons`, but warms the icons of the apps that are or are about to be visible first
+pub fn preprocess_icons_visible_first(
+	apps: &[Application],
+	is_visible: impl Fn(usize, &Application) -> bool,
+	preferred_px_size: u16,
+	cancel: &CancelToken,
+) -> usize {
+	preprocess_icons(
+		icon_warming_order(apps, is_visible),
+		preferred_px_size,
+		cancel,
+	)
+}
+
+#[test]
+fn test_icon_warming_order() {
+	let apps: Vec<_> = (0..6)
+		.map(|i| {
+			Application::create(DesktopFile {
+				name: Some(format!("App {}", i)),
+				..Default::default()
+			})
+			.unwrap()
+		})
+		.collect();
+
+	// Pages of 2, the second page is up next
+	let next_page = |i: usize, _: &Application| i / 2 == 1;
+	let names: Vec<_> = icon_warming_order(&apps, next_page)
+		.into_iter()
+		.map(|app| app.name().unwrap())
+		.collect();
+	assert_eq!(
+		names,
+		["App 2", "App 3", "App 0", "App 1", "App 4", "App 5"]
+	);
+
+	// Cancelling once the visible ones are done leaves the rest cold
+	let cancel = CancelToken::new();
+	let processed = preprocess_icons(
+		icon_warming_order(&apps, next_page)
+			.into_iter()
+			.enumerate()
+			.map(|(i, app)| {
+				if i == 2 {
+					cancel.cancel();
+				}
+				app
+			}),
+		64,
+		&cancel,
+	);
+	assert_eq!(processed, 2);
+	assert_eq!(
+		preprocess_icons_visible_first(&apps, next_page, 64, &CancelToken::new()),
+		6
+	);
+}
+
+/// Draws the icon of every app with its name under it on a grid, and saves it as a PNG at `png_path`.
+/// Meant for checking the icon set of a deployment, apps without an icon get an empty cell.
+/// Returns how many icons were drawn.
+pub fn write_contact_sheet<'a>(
+	apps: impl IntoIterator<Item = &'a Application>,
+	icon_size: u16,
+	png_path: impl AsRef<Path>,
+) -> Result<usize, std::io::Error> {
+	let apps: Vec<_> = apps.into_iter().collect();
+	let columns = ((apps.len() as f32).sqrt().ceil() as usize).max(1);
+	let rows = ((apps.len() + columns - 1) / columns).max(1);
+	let icon_size_px = icon_size as f32;
+	let cell_width = icon_size_px * 1.5;
+	let label_height = icon_size_px * 0.25;
+	let cell_height = cell_width + label_height;
+	let width = cell_width * columns as f32;
+	let height = cell_height * rows as f32;
+
+	let mut svg = format!(
+		r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{width}" height="{height}"><rect width="100%" height="100%" fill="#202020"/>"##
+	);
+	let mut drawn = 0;
+	for (i, app) in apps.iter().enumerate() {
+		let x = (i % columns) as f32 * cell_width;
+		let y = (i / columns) as f32 * cell_height;
+		let padding = (cell_width - icon_size_px) / 2.0;
+		let png = app
+			.try_backend_ready_icon(icon_size, false)
+			.ok()
+			.filter(|icon| icon.icon_type == IconType::Png)
+			.and_then(|icon| std::fs::read(icon.path).ok());
+		if let Some(png) = png {
+			svg.push_str(&format!(
+				r#"<image x="{}" y="{}" width="{icon_size}" height="{icon_size}" xlink:href="data:image/png;base64,{}"/>"#,
+				x + padding,
+				y + padding,
+				BASE64.encode(png)
+			));
+			drawn += 1;
+		}
+		let label = app.name().map(str::to_string).unwrap_or_else(|| app.id());
+		svg.push_str(&format!(
+			r##"<text x="{}" y="{}" font-size="{}" font-family="sans-serif" text-anchor="middle" fill="#ffffff">{}</text>"##,
+			x + cell_width / 2.0,
+			y + cell_width + label_height * 0.5,
+			label_height * 0.6,
+			escape_xml(&label)
+		));
+	}
+	svg.push_str("</svg>");
+
+	let mut tree =
+		Tree::from_data(svg.as_bytes(), &Options::default()).map_err(|_| ErrorKind::InvalidData)?;
+	let mut fonts = fontdb::Database::new();
+	fonts.load_system_fonts();
+	tree.convert_text(&fonts);
+	let mut pixmap =
+		Pixmap::new(width.ceil() as u32, height.ceil() as u32).ok_or(ErrorKind::InvalidInput)?;
+	resvg::render(
+		&tree,
+		FitTo::Original,
+		Transform::identity(),
+		pixmap.as_mut(),
+	);
+	pixmap
+		.save_png(png_path)
+		.map_err(|_| ErrorKind::InvalidData)?;
+	Ok(drawn)
+}
+
+/// Packs the PNG icons of `apps` into texture atlas pages in `dir`, looked up by app id.
+/// Apps without a PNG icon at that size aren't in the atlas.
+pub fn write_icon_atlas<'a>(
+	apps: impl IntoIterator<Item = &'a Application>,
+	icon_size: u16,
+	page_size: u32,
+	dir: impl AsRef<Path>,
+) -> Result<Atlas, std::io::Error> {
+	let icons = apps.into_iter().filter_map(|app| {
+		let icon = app.try_backend_ready_icon(icon_size, false).ok()?;
+		(icon.icon_type == IconType::Png).then(|| (app.id(), icon.path))
+	});
+	build_atlas(icons, page_size, dir)
+}
+
+fn escape_xml(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+#[test]
+fn test_write_contact_sheet() {
+	use image::GenericImageView;
+	let _env = crate::test_utils::lock_env();
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let app = |name: &str, color: Option<&str>| {
+		let icon = color.map(|color| {
+			let path = dir.path().join(format!("contact-sheet-{}.svg", name));
+			std::fs::write(
+				&path,
+				format!("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"10\" height=\"10\" fill=\"{}\"/></svg>", color),
+			)
+			.unwrap();
+			path.to_string_lossy().into_owned()
+		});
+		Application::create(DesktopFile {
+			name: Some(name.to_string()),
+			icon,
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = [
+		app("Red & Co", Some("#ff0000")),
+		app("Blue", Some("#0000ff")),
+		app("Iconless", None),
+	];
+
+	let sheet_path = dir.path().join("sheet.png");
+	assert_eq!(write_contact_sheet(&apps, 32, &sheet_path).unwrap(), 2);
+	let sheet = image::open(&sheet_path).unwrap();
+	// 2x2 grid of 48px wide cells with 8px tall labels
+	assert_eq!(sheet.dimensions(), (96, 112));
+	assert_eq!(sheet.get_pixel(24, 24).0, [255, 0, 0, 255]);
+	assert_eq!(sheet.get_pixel(48 + 24, 24).0, [0, 0, 255, 255]);
+}
+
+/// Sorts `apps` from the most recently installed or updated to the oldest, by their desktop file
+pub fn sort_by_modified(apps: &mut [Application]) {
+	apps.sort_by_key(|app| std::cmp::Reverse(app.modified()));
+}
+
+#[test]
+fn test_sort_by_modified() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let app = |name: &str, age_secs: u64| {
+		let path = dir.path().join(format!("{}.desktop", name));
+		std::fs::write(
+			&path,
+			format!("[Desktop Entry]\nName={}\nExec={}", name, name),
+		)
+		.unwrap();
+		std::fs::File::options()
+			.write(true)
+			.open(&path)
+			.unwrap()
+			.set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+			.unwrap();
+		Application::create(crate::xdg::parse_desktop_file(path).unwrap()).unwrap()
+	};
+	let mut apps = vec![app("old", 3600), app("new", 10), app("middle", 600)];
+
+	sort_by_modified(&mut apps);
+	let names: Vec<_> = apps.iter().map(|app| app.name().unwrap()).collect();
+	assert_eq!(names, ["new", "middle", "old"]);
+}
+
+/// The order of `apps` as a JSON list of their IDs, to be restored elsewhere with `apply_layout_order`
+pub fn export_layout(apps: &[Application]) -> String {
+	let ids: Vec<String> = apps.iter().map(Application::id).collect();
+	serde_json::to_string_pretty(&ids).unwrap()
+}
+
+/// Reorders `apps` to match a layout from `export_layout`.
+/// Apps the layout doesn't mention go after the ones it does, in the order they were in.
+pub fn apply_layout_order(apps: &mut [Application], layout: &str) -> Result<(), serde_json::Error> {
+	let ids: Vec<String> = serde_json::from_str(layout)?;
+	let positions: HashMap<&str, usize> = ids
+		.iter()
+		.enumerate()
+		.map(|(i, id)| (id.as_str(), i))
+		.collect();
+	apps.sort_by_cached_key(|app| {
+		positions
+			.get(app.id().as_str())
+			.copied()
+			.unwrap_or(usize::MAX)
+	});
+	Ok(())
+}
+
+#[test]
+fn test_apply_layout_order() {
+	let app = |id: &str| {
+		Application::create(DesktopFile {
+			path: PathBuf::from(format!("/usr/share/applications/{}.desktop", id)),
+			name: Some(id.to_string()),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let ids = |apps: &[Application]| -> Vec<String> { apps.iter().map(Application::id).collect() };
+	let arranged = [app("c"), app("a"), app("b")];
+	let layout = export_layout(&arranged);
+
+	let mut discovered = vec![app("a"), app("new"), app("b"), app("c")];
+	apply_layout_order(&mut discovered, &layout).unwrap();
+	assert_eq!(
+		ids(&discovered),
+		["c.desktop", "a.desktop", "b.desktop", "new.desktop"]
+	);
+	assert_eq!(export_layout(&discovered[..3]), layout);
+
+	assert!(apply_layout_order(&mut discovered, "not json").is_err());
+}
+
+/// The labels of `apps` in order, like `Application::display_name`, but apps sharing a name get told apart
+/// by their generic name, or by their ID if that's the same too, eg: `Settings (System Settings)`
+pub fn disambiguated_labels(
+	apps: &[Application],
+	locale: Option<&str>,
+	prefer_full_name: bool,
+) -> Vec<Option<String>> {
+	let names: Vec<Option<&str>> = apps
+		.iter()
+		.map(|app| app.display_name(locale, prefer_full_name))
+		.collect();
+	let mut name_counts: HashMap<&str, usize> = HashMap::new();
+	for name in names.iter().flatten() {
+		*name_counts.entry(*name).or_default() += 1;
+	}
+	let suffixes = |app: &Application| -> Vec<String> {
+		let id = app.id();
+		let stem = id.strip_suffix(".desktop").unwrap_or(&id).to_string();
+		let short_id = stem.rsplit('.').next().unwrap_or(&stem).to_string();
+		let generic_name = app
 			.desktop_file
-			.command
-			.clone()
-			.ok_or(NodeError::DoesNotExist)?;
-		tokio::task::spawn(async move {
-			let Ok(startup_token) = future_startup_token.await else {return};
-			let Ok(connection_env) = future_connection_env.await else {return};
-			dbg!(&connection_env);
-			for (k, v) in connection_env.into_iter() {
-				std::env::set_var(k, v);
+			.localized_generic_name(locale)
+			.map(str::to_string);
+		generic_name.into_iter().chain([short_id, stem]).collect()
+	};
+
+	apps.iter()
+		.zip(&names)
+		.map(|(app, name)| {
+			let name = (*name)?;
+			if name_counts[name] < 2 {
+				return Some(name.to_string());
 			}
+			// The first suffix no other app with the same name would get too
+			let others: Vec<Vec<String>> = apps
+				.iter()
+				.zip(&names)
+				.filter(|(other, other_name)| {
+					*other_name == &Some(name) && !std::ptr::eq(*other, app)
+				})
+				.map(|(other, _)| suffixes(other))
+				.collect();
+			let suffixes = suffixes(app);
+			let suffix = suffixes
+				.iter()
+				.find(|suffix| {
+					suffix.as_str() != name && !others.iter().any(|other| other.contains(suffix))
+				})
+				.or(suffixes.last())?;
+			Some(format!("{} ({})", name, suffix))
+		})
+		.collect()
+}
 
-			std::env::set_var("STARDUST_STARTUP_TOKEN", startup_token);
-			let re = Regex::new(r"%[fFuUdDnNickvm]").unwrap();
-			let exec = re.replace_all(&executable, "");
-			unsafe {
-				Command::new("sh")
-					.arg("-c")
-					.arg(exec.to_string())
-					.stdin(Stdio::null())
-					.stdout(Stdio::null())
-					.stderr(Stdio::null())
-					.pre_exec(|| {
-						_ = setsid();
-						Ok(())
+#[test]
+fn test_disambiguated_labels() {
+	let app = |id: &str, name: &str, generic_name: Option<&str>| {
+		Application::create(DesktopFile {
+			path: PathBuf::from(format!("/usr/share/applications/{}.desktop", id)),
+			name: Some(name.to_string()),
+			generic_name: generic_name.map(str::to_string),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = [
+		app("org.gnome.Settings", "Settings", Some("System Settings")),
+		app("com.example.Settings", "Settings", Some("Game Settings")),
+		app("org.mozilla.firefox", "Firefox", Some("Web Browser")),
+		app("org.kde.editor", "Editor", Some("Text Editor")),
+		app("org.gnome.editor", "Editor", Some("Text Editor")),
+		app("org.xfce.mousepad", "Mousepad", None),
+		app("org.other.mousepad", "Mousepad", None),
+	];
+	let labels = disambiguated_labels(&apps, Some("en"), false);
+	assert_eq!(
+		labels,
+		[
+			Some("Settings (System Settings)".to_string()),
+			Some("Settings (Game Settings)".to_string()),
+			Some("Firefox".to_string()),
+			// Same generic name, the short ID is the same too
+			Some("Editor (org.kde.editor)".to_string()),
+			Some("Editor (org.gnome.editor)".to_string()),
+			Some("Mousepad (org.xfce.mousepad)".to_string()),
+			Some("Mousepad (org.other.mousepad)".to_string()),
+		]
+	);
+	let unique: HashSet<_> = labels.iter().collect();
+	assert_eq!(unique.len(), labels.len());
+}
+
+/// IDs of the applications launched by protostar that are still running, going by the
+/// `LAUNCHED_ID_ENV` of every process that can be read in `/proc`
+pub fn running_app_ids() -> HashSet<String> {
+	running_app_ids_in(Path::new("/proc"))
+}
+
+fn running_app_ids_in(proc_dir: &Path) -> HashSet<String> {
+	let prefix = format!("{}=", LAUNCHED_ID_ENV);
+	std::fs::read_dir(proc_dir)
+		.into_iter()
+		.flatten()
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+		// Processes of other users can't be read, and they're not ours to switch to anyway
+		.filter_map(|entry| std::fs::read(entry.path().join("environ")).ok())
+		.filter_map(|environ| {
+			environ
+				.split(|byte| *byte == 0)
+				.find_map(|var| var.strip_prefix(prefix.as_bytes()))
+				.map(|id| String::from_utf8_lossy(id).into_owned())
+		})
+		.collect()
+}
+
+/// Moves the applications whose ID is in `running` (eg: from `running_app_ids`) to the front,
+/// the order is kept otherwise, so the launcher doubles as a task switcher
+pub fn sort_running_first(apps: &mut [Application], running: &HashSet<String>) {
+	apps.sort_by_cached_key(|app| !running.contains(&app.id()));
+}
+
+#[test]
+fn test_sort_running_first() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let process = |pid: &str, environ: &[&str]| {
+		let process_dir = dir.path().join(pid);
+		std::fs::create_dir_all(&process_dir).unwrap();
+		std::fs::write(process_dir.join("environ"), environ.join("\0")).unwrap();
+	};
+	process(
+		"100",
+		&["HOME=/home/user", "PROTOSTAR_LAUNCHED_ID=editor.desktop"],
+	);
+	process("200", &["HOME=/home/user"]);
+	process("self", &["PROTOSTAR_LAUNCHED_ID=not-a-process.desktop"]);
+	let running = running_app_ids_in(dir.path());
+	assert_eq!(running, HashSet::from(["editor.desktop".to_string()]));
+
+	let app = |id: &str| {
+		Application::create(DesktopFile {
+			path: PathBuf::from(format!("/usr/share/applications/{}.desktop", id)),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let mut apps = vec![app("browser"), app("files"), app("editor"), app("terminal")];
+	sort_running_first(&mut apps, &running);
+	let ids: Vec<_> = apps.iter().map(Application::id).collect();
+	assert_eq!(
+		ids,
+		[
+			"editor.desktop",
+			"browser.desktop",
+			"files.desktop",
+			"terminal.desktop"
+		]
+	);
+}
+
+/// Every action of every app next to its app, eg: for a command palette
+pub fn all_actions(apps: &[Application]) -> Vec<(Application, DesktopAction)> {
+	apps.iter()
+		.flat_map(|app| {
+			app.actions()
+				.iter()
+				.map(move |action| (app.clone(), action.clone()))
+		})
+		.collect()
+}
+
+#[test]
+fn test_all_actions() {
+	let app = |name: &str, action_ids: &[&str]| {
+		Application::create(DesktopFile {
+			name: Some(name.to_string()),
+			actions: action_ids
+				.iter()
+				.map(|id| DesktopAction {
+					id: id.to_string(),
+					..Default::default()
+				})
+				.collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = [
+		app("Browser", &["new-window", "private"]),
+		app("Calculator", &[]),
+		app("Editor", &["new-file"]),
+	];
+
+	let actions: Vec<_> = all_actions(&apps)
+		.into_iter()
+		.map(|(app, action)| (app.name().unwrap().to_string(), action.id))
+		.collect();
+	assert_eq!(
+		actions,
+		[
+			("Browser".to_string(), "new-window".to_string()),
+			("Browser".to_string(), "private".to_string()),
+			("Editor".to_string(), "new-file".to_string()),
+		]
+	);
+}
+
+/// What changed between two discovery passes
+#[derive(Debug, Clone, Default)]
+pub struct AppDiff {
+	/// Only in the new pass
+	pub added: Vec<Application>,
+	/// Only in the old pass
+	pub removed: Vec<Application>,
+	/// In both passes with a different desktop file, as they are in the new pass
+	pub changed: Vec<Application>,
+}
+impl AppDiff {
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+	}
+}
+
+/// Compares two discovery passes by desktop file ID, and everything that was parsed (modification
+/// time included) for the IDs in both
+pub fn diff_applications(old: &[Application], new: &[Application]) -> AppDiff {
+	let old_by_id: HashMap<_, _> = old.iter().map(|app| (app.id(), app)).collect();
+	let new_ids: HashSet<_> = new.iter().map(|app| app.id()).collect();
+	let mut diff = AppDiff::default();
+	for app in new {
+		match old_by_id.get(&app.id()) {
+			None => diff.added.push(app.clone()),
+			Some(old_app) if old_app.desktop_file != app.desktop_file => {
+				diff.changed.push(app.clone())
+			}
+			Some(_) => (),
+		}
+	}
+	diff.removed = old
+		.iter()
+		.filter(|app| !new_ids.contains(&app.id()))
+		.cloned()
+		.collect();
+	diff
+}
+
+#[test]
+fn test_diff_applications() {
+	let app = |id: &str, name: &str| {
+		Application::create(
+			DesktopFile::from_bytes(
+				format!("[Desktop Entry]\nName={}\nExec={}", name, id).as_bytes(),
+				format!("{}.desktop", id),
+			)
+			.unwrap(),
+		)
+		.unwrap()
+	};
+	let old = [
+		app("editor", "Editor"),
+		app("browser", "Browser"),
+		app("mail", "Mail"),
+	];
+	let new = [
+		app("editor", "Editor"),
+		app("mail", "Mail Client"),
+		app("music", "Music"),
+	];
+
+	let ids = |apps: &[Application]| apps.iter().map(|app| app.id()).collect::<Vec<_>>();
+	let diff = diff_applications(&old, &new);
+	assert_eq!(ids(&diff.added), ["music.desktop"]);
+	assert_eq!(ids(&diff.removed), ["browser.desktop"]);
+	assert_eq!(ids(&diff.changed), ["mail.desktop"]);
+	assert_eq!(diff.changed[0].name(), Some("Mail Client"));
+	assert!(diff_applications(&new, &new).is_empty());
+}
+
+/// Finds the application a window with `wm_class` most likely belongs to, by its `StartupWMClass`
+/// or, since a lot of applications don't set it, by the name of its executable
+pub fn find_by_wm_class(apps: &[Application], wm_class: &str) -> Option<Application> {
+	apps.iter()
+		.find(|app| {
+			app.startup_wm_class()
+				.map_or(false, |class| class.eq_ignore_ascii_case(wm_class))
+		})
+		.or_else(|| {
+			apps.iter().find(|app| {
+				app.program()
+					.as_ref()
+					.and_then(|program| Path::new(program).file_name())
+					.map_or(false, |name| {
+						name.to_string_lossy().eq_ignore_ascii_case(wm_class)
 					})
-					.spawn()
-					.expect("Failed to start child process");
+			})
+		})
+		.cloned()
+}
+
+/// The applications whose icon can't be resolved, to know which ones need an icon override
+pub fn applications_without_icons(
+	apps: &[Application],
+	preferred_px_size: u16,
+) -> Vec<Application> {
+	applications_without_icons_with(apps, &IconResolver::default(), preferred_px_size)
+}
+/// Like `applications_without_icons`, with the icons resolved the way `resolver` is set up to
+pub fn applications_without_icons_with(
+	apps: &[Application],
+	resolver: &IconResolver,
+	preferred_px_size: u16,
+) -> Vec<Application> {
+	apps.iter()
+		.filter(|app| {
+			app.try_icon_with(resolver, preferred_px_size, false)
+				.is_err()
+		})
+		.cloned()
+		.collect()
+}
+
+#[test]
+fn test_applications_without_icons() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let icon_path = dir.path().join("icon.png");
+	std::fs::write(&icon_path, []).unwrap();
+	let apps = vec![
+		Application::create(DesktopFile {
+			name: Some("Iconic".into()),
+			icon: Some(icon_path.to_string_lossy().into_owned()),
+			..Default::default()
+		})
+		.unwrap(),
+		Application::create(DesktopFile {
+			name: Some("Iconless".into()),
+			..Default::default()
+		})
+		.unwrap(),
+	];
+
+	// Remembered in a cache of its own, not in the one of the user
+	let resolver = IconResolver::new().cache_dir(dir.path().join("cache"));
+	let without_icons = applications_without_icons_with(&apps, &resolver, 64);
+	assert_eq!(without_icons.len(), 1);
+	assert_eq!(without_icons[0].name(), Some("Iconless"));
+}
+
+/// Every discovered application whose desktop file has warnings, with them, to see all the
+/// problematic entries at once instead of validating them one by one
+pub fn applications_with_warnings(
+	config: &DiscoveryConfig,
+) -> Vec<(Application, Vec<DesktopFileWarning>)> {
+	discover_applications(config)
+		.into_iter()
+		.filter_map(|desktop_file| {
+			let warnings = validate_desktop_file(&desktop_file);
+			if warnings.is_empty() {
+				return None;
 			}
-		});
+			Some((Application::create(desktop_file).ok()?, warnings))
+		})
+		.collect()
+}
 
-		Ok(())
+#[test]
+fn test_applications_with_warnings() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	std::fs::write(
+		dir.path().join("fine.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Fine\nExec=fine",
+	)
+	.unwrap();
+	std::fs::write(
+		dir.path().join("legacy.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Legacy\nEncoding=UTF-8\nExec=legacy %x",
+	)
+	.unwrap();
+	let config = DiscoveryConfig::new().root_dirs([dir.path().to_path_buf()]);
+
+	let with_warnings = applications_with_warnings(&config);
+	assert_eq!(with_warnings.len(), 1);
+	let (application, warnings) = &with_warnings[0];
+	assert_eq!(application.name(), Some("Legacy"));
+	assert_eq!(
+		warnings,
+		&[
+			DesktopFileWarning::DeprecatedKey("Encoding".into()),
+			DesktopFileWarning::UnknownFieldCode("%x".into()),
+		]
+	);
+}
+
+#[test]
+fn test_category_icon_fallback() {
+	let xdg = crate::test_utils::FakeXdg::new();
+	xdg.add_icon_theme("hicolor", &[], &["48x48/apps"]);
+	let category_icon = xdg.add_icon("hicolor", "48x48/apps", "applications-development.png", &[]);
+	let ide = Application::create(DesktopFile {
+		name: Some("IDE".into()),
+		categories: vec!["Development".into(), "IDE".into()],
+		..Default::default()
+	})
+	.unwrap();
+	assert!(matches!(
+		ide.try_icon(48, false),
+		Err(IconError::NotDeclared)
+	));
+
+	let resolver = IconResolver::new()
+		.cache_dir(xdg.cache_home())
+		.category_icon_fallback(true);
+	let icon = ide.try_icon_with(&resolver, 48, false);
+	let uncategorized = Application::create(DesktopFile {
+		name: Some("Thing".into()),
+		..Default::default()
+	})
+	.unwrap()
+	.try_icon_with(&resolver, 48, false);
+	assert_eq!(icon.unwrap().path, category_icon);
+	assert!(matches!(uncategorized, Err(IconError::NotDeclared)));
+}
+
+#[test]
+fn test_backend_ready_icon_webp() {
+	let _env = crate::test_utils::lock_env();
+	// 1x1 lossy WebP
+	const WEBP: [u8; 42] = [
+		0x52, 0x49, 0x46, 0x46, 0x22, 0x00, 0x00, 0x00, 0x57, 0x45, 0x42, 0x50, 0x56, 0x50, 0x38,
+		0x20, 0x16, 0x00, 0x00, 0x00, 0x30, 0x01, 0x00, 0x9d, 0x01, 0x2a, 0x01, 0x00, 0x01, 0x00,
+		0x0e, 0xc0, 0xfe, 0x25, 0xa4, 0x00, 0x03, 0x70, 0x00, 0x00, 0x00, 0x00,
+	];
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let icon_path = dir.path().join("webp_only.webp");
+	std::fs::write(&icon_path, WEBP).unwrap();
+	let application = Application::create(DesktopFile {
+		icon: Some(icon_path.to_string_lossy().into_owned()),
+		..Default::default()
+	})
+	.unwrap();
+
+	let icon = application.backend_ready_icon(16, false).unwrap();
+	assert_eq!(icon.icon_type, IconType::Png);
+	assert_eq!(icon.path.extension().unwrap(), "png");
+	assert!(icon.path.exists());
+}
+
+#[test]
+fn test_find_by_wm_class() {
+	let apps = vec![
+		Application::create(DesktopFile {
+			name: Some("Browser".into()),
+			command: Some("/usr/lib/browser/launch %u".into()),
+			startup_wm_class: Some("WebBrowser".into()),
+			..Default::default()
+		})
+		.unwrap(),
+		Application::create(DesktopFile {
+			name: Some("Editor".into()),
+			command: Some("/usr/bin/editor %F".into()),
+			..Default::default()
+		})
+		.unwrap(),
+	];
+
+	let browser = find_by_wm_class(&apps, "webbrowser").unwrap();
+	assert_eq!(browser.name(), Some("Browser"));
+	let editor = find_by_wm_class(&apps, "Editor").unwrap();
+	assert_eq!(editor.name(), Some("Editor"));
+	assert!(find_by_wm_class(&apps, "unknown").is_none());
+}
+
+#[test]
+fn test_create_hidden_application() {
+	let desktop_file = DesktopFile {
+		name: Some("Hidden".into()),
+		no_display: true,
+		..Default::default()
+	};
+	assert!(Application::create(desktop_file).is_err());
+}
+
+#[test]
+fn test_resolved_executable() {
+	let _env = crate::test_utils::lock_env();
+	let application = Application::create(DesktopFile {
+		command: Some("sh -c true".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let executable = application.resolved_executable().unwrap();
+	assert!(executable.is_absolute());
+	assert!(executable.ends_with("sh"));
+}
+
+#[test]
+fn test_launch_command_env() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let path = dir.path().join("org.example.App.desktop");
+	std::fs::write(&path, "[Desktop Entry]\nName=Example\nExec=example %U\n").unwrap();
+	let application = Application::create(crate::xdg::parse_desktop_file(path).unwrap()).unwrap();
+	let command = application
+		.launch_command(
+			&LaunchOptions::default(),
+			[("FLAT_CLIENT".to_string(), "1".to_string())],
+			Some("token"),
+		)
+		.unwrap();
+	let envs: Vec<_> = command.get_envs().collect();
+	assert!(envs.contains(&(
+		LAUNCHED_ID_ENV.as_ref(),
+		Some("org.example.App.desktop".as_ref())
+	)));
+	assert!(envs.contains(&("STARDUST_STARTUP_TOKEN".as_ref(), Some("token".as_ref()))));
+	assert!(envs.contains(&("FLAT_CLIENT".as_ref(), Some("1".as_ref()))));
+	assert!(std::env::var_os(LAUNCHED_ID_ENV).is_none());
+	assert!(!envs.iter().any(|(key, _)| *key == LAUNCHED_WM_CLASS_ENV));
+}
+
+#[test]
+fn test_launch_command_wm_class_env() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Example\nExec=example\nStartupWMClass=example-window\n",
+		"org.example.App.desktop",
+	)
+	.unwrap();
+	let application = Application::create(desktop_file).unwrap();
+	let command = application
+		.launch_command(&LaunchOptions::default(), [], None)
+		.unwrap();
+	let envs: Vec<_> = command.get_envs().collect();
+	assert!(envs.contains(&(
+		LAUNCHED_WM_CLASS_ENV.as_ref(),
+		Some("example-window".as_ref())
+	)));
+}
+
+#[test]
+fn test_launch_command_sanitize_display() {
+	let application = Application::create(DesktopFile {
+		command: Some("editor".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let connection_env = || {
+		[(
+			"WAYLAND_DISPLAY".to_string(),
+			"wayland-stardust".to_string(),
+		)]
+	};
+	let options = LaunchOptions {
+		sanitize_display: true,
+		..Default::default()
+	};
+	let command = application
+		.launch_command(&options, connection_env(), None)
+		.unwrap();
+	let envs: Vec<_> = command.get_envs().collect();
+	// Removed, unless the connection environment sets them again
+	assert!(envs.contains(&("DISPLAY".as_ref(), None)));
+	assert!(envs.contains(&(
+		"WAYLAND_DISPLAY".as_ref(),
+		Some("wayland-stardust".as_ref())
+	)));
+
+	let command = application
+		.launch_command(&LaunchOptions::default(), connection_env(), None)
+		.unwrap();
+	assert!(!command.get_envs().any(|(key, _)| key == "DISPLAY"));
+}
+
+#[test]
+fn test_launch_command_scaling_env() {
+	let application = Application::create(DesktopFile {
+		command: Some("editor".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		scaling_env: scaling_env(1.5),
+		..Default::default()
+	};
+	let command = application.launch_command(&options, [], None).unwrap();
+	let envs: Vec<_> = command.get_envs().collect();
+	assert!(envs.contains(&("GDK_SCALE".as_ref(), Some("2".as_ref()))));
+	assert!(envs.contains(&("GDK_DPI_SCALE".as_ref(), Some("0.75".as_ref()))));
+	assert!(envs.contains(&("QT_SCALE_FACTOR".as_ref(), Some("1.5".as_ref()))));
+}
+
+#[test]
+fn test_launch_command_extra_args() {
+	let application = Application::create(DesktopFile {
+		command: Some("editor --new-window %F".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		extra_args: vec!["--verbose".into(), "two words".into()],
+		..Default::default()
+	};
+	let command = application.launch_command(&options, [], None).unwrap();
+	let args: Vec<_> = command.get_args().collect();
+	assert_eq!(
+		args,
+		[
+			"-c",
+			"editor --new-window  \"$@\"",
+			"sh",
+			"--verbose",
+			"two words"
+		]
+	);
+}
+
+#[test]
+fn test_launch_command_without_shell() {
+	// Other tests point `$HOME` elsewhere while they run
+	let xdg = crate::test_utils::FakeXdg::new();
+	let application = Application::create(DesktopFile {
+		command: Some("$HOME/bin/app --open \"two words\" %U".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		extra_args: vec!["--verbose".into()],
+		without_shell: true,
+		..Default::default()
+	};
+	let command = application.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_program(), xdg.home().join("bin").join("app"));
+	let args: Vec<_> = command.get_args().collect();
+	assert_eq!(args, ["--open", "two words", "--verbose"]);
+
+	let command = |exec: &str| {
+		Application::create(DesktopFile {
+			command: Some(exec.into()),
+			..Default::default()
+		})
+		.unwrap()
+		.launch_command(&options, [], None)
+		.unwrap()
+	};
+	let direct = command("foo --bar");
+	assert_eq!(direct.get_program(), "foo");
+	let args: Vec<_> = direct.get_args().collect();
+	assert_eq!(args, ["--bar", "--verbose"]);
+	let shell = command("foo && bar");
+	assert_eq!(shell.get_program(), "sh");
+	let args: Vec<_> = shell.get_args().collect();
+	assert_eq!(args, ["-c", "foo && bar \"$@\"", "sh", "--verbose"]);
+}
+
+#[test]
+fn test_launch_command_quoted_field_code() {
+	let player = Application::create(DesktopFile {
+		command: Some("player \"%f\"".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		files: vec!["/music/My Song.ogg".into()],
+		without_shell: true,
+		..Default::default()
+	};
+	let command = player.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_program(), "player");
+	let args: Vec<_> = command.get_args().collect();
+	assert_eq!(args, ["/music/My Song.ogg"]);
+
+	let through_shell = LaunchOptions {
+		without_shell: false,
+		..options
+	};
+	let command = player.launch_command(&through_shell, [], None).unwrap();
+	let args: Vec<_> = command.get_args().collect();
+	assert_eq!(args, ["-c", "player \"/music/My Song.ogg\"", "sh"]);
+
+	// Without files the quoted field code is dropped rather than passed empty
+	let command = player
+		.launch_command(
+			&LaunchOptions {
+				without_shell: true,
+				..Default::default()
+			},
+			[],
+			None,
+		)
+		.unwrap();
+	assert_eq!(command.get_args().count(), 0);
+}
+
+#[test]
+fn test_launch_command_working_dir() {
+	let application = |contents: &str| {
+		Application::create(DesktopFile::from_bytes(contents.as_bytes(), "app.desktop").unwrap())
+			.unwrap()
+	};
+	let without_path = application("[Desktop Entry]\nName=App\nExec=app\n");
+	let with_path = application("[Desktop Entry]\nName=App\nExec=app\nPath=/opt/app\n");
+	let options = LaunchOptions {
+		default_working_dir: Some("/home/user".into()),
+		..Default::default()
+	};
+
+	let command = without_path.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_current_dir(), Some(Path::new("/home/user")));
+	let command = with_path.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_current_dir(), Some(Path::new("/opt/app")));
+	let command = without_path
+		.launch_command(&LaunchOptions::default(), [], None)
+		.unwrap();
+	assert_eq!(command.get_current_dir(), None);
+}
+
+#[test]
+fn test_launch_command_discrete_gpu() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Game\nExec=game --fullscreen\nPrefersNonDefaultGPU=true\n",
+		"game.desktop",
+	)
+	.unwrap();
+	assert!(desktop_file.prefers_non_default_gpu);
+	let game = Application::create(desktop_file).unwrap();
+	let editor = Application::create(DesktopFile {
+		command: Some("editor".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		discrete_gpu_wrapper: Some(vec!["prime-run".into()]),
+		..Default::default()
+	};
+
+	let command = game.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_program(), "prime-run");
+	let args: Vec<_> = command.get_args().collect();
+	assert_eq!(args, ["sh", "-c", "game --fullscreen", "sh"]);
+
+	let command = editor.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_program(), "sh");
+	let command = game
+		.launch_command(&LaunchOptions::default(), [], None)
+		.unwrap();
+	assert_eq!(command.get_program(), "sh");
+}
+
+#[test]
+fn test_launch_command_transform_argv() {
+	let application = Application::create(DesktopFile {
+		command: Some("editor --new-window".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		without_shell: true,
+		extra_args: vec!["notes.txt".into()],
+		transform_argv: Some(wrap_with(vec!["firejail".into(), "--quiet".into()])),
+		..Default::default()
+	};
+	let command = application.launch_command(&options, [], None).unwrap();
+	assert_eq!(command.get_program(), "firejail");
+	let args: Vec<_> = command.get_args().collect();
+	assert_eq!(args, ["--quiet", "editor", "--new-window", "notes.txt"]);
+
+	let options = LaunchOptions {
+		transform_argv: Some(Arc::new(|argv: Vec<String>| {
+			argv.into_iter()
+				.filter(|arg| arg != "--new-window")
+				.collect()
+		})),
+		..options
+	};
+	let args: Vec<_> = application
+		.launch_command(&options, [], None)
+		.unwrap()
+		.get_args()
+		.map(|arg| arg.to_owned())
+		.collect();
+	assert_eq!(args, ["notes.txt"]);
+
+	let cancelled = LaunchOptions {
+		transform_argv: Some(Arc::new(|_| Vec::new())),
+		..Default::default()
+	};
+	assert!(application.launch_command(&cancelled, [], None).is_none());
+}
+
+#[test]
+fn test_launch_command_niceness_umask() {
+	let _env = crate::test_utils::lock_env();
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let out = |name: &str| dir.path().join(name);
+	let application = Application::create(DesktopFile {
+		command: Some(format!(
+			"umask > {}; cut -d ' ' -f 19 /proc/self/stat > {}",
+			out("umask").display(),
+			out("nice").display()
+		)),
+		..Default::default()
+	})
+	.unwrap();
+	let options = LaunchOptions {
+		niceness: Some(19),
+		umask: Some(0o077),
+		..Default::default()
+	};
+	let status = application
+		.launch_command(&options, [], None)
+		.unwrap()
+		.status()
+		.unwrap();
+	assert!(status.success());
+	let read = |name: &str| std::fs::read_to_string(out(name)).unwrap();
+	assert_eq!(read("umask").trim(), "0077");
+	assert_eq!(read("nice").trim(), "19");
+}
+
+#[test]
+fn test_is_runnable_script() {
+	use std::os::unix::fs::PermissionsExt;
+	let _env = crate::test_utils::lock_env();
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let script = dir.path().join("script.sh");
+	std::fs::write(&script, "#!/bin/sh\necho hi\n").unwrap();
+	let application = Application::create(DesktopFile {
+		command: Some(format!("{} --flag", script.display())),
+		..Default::default()
+	})
+	.unwrap();
+
+	assert!(!application.is_runnable());
+	std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+	assert!(application.is_runnable());
+	assert_eq!(application.resolved_executable(), Some(script));
+
+	let missing_try_exec = Application::create(DesktopFile {
+		command: Some("sh -c true".into()),
+		try_exec: Some("surely-not-a-real-program".into()),
+		..Default::default()
+	})
+	.unwrap();
+	assert!(!missing_try_exec.is_runnable());
+}
+
+#[test]
+fn test_media_kind() {
+	let app = |mime_types: &[&str]| {
+		Application::create(DesktopFile {
+			mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	assert_eq!(app(&["audio/mpeg"]).media_kind(), Some(MediaKind::Audio));
+	assert_eq!(
+		app(&["video/mp4", "audio/mpeg", "video/webm"]).media_kind(),
+		Some(MediaKind::Video)
+	);
+	assert_eq!(
+		app(&["image/png", "audio/ogg"]).media_kind(),
+		Some(MediaKind::Audio)
+	);
+	assert_eq!(app(&["x-scheme-handler/https"]).media_kind(), None);
+	assert_eq!(app(&[]).media_kind(), None);
+	assert_eq!(MediaKind::Audio.icon_name(), "audio-x-generic");
+}
+
+/// Buckets the applications by their `media_kind`, eg: for a media launcher.
+/// Applications that don't open any kind of media are left out.
+pub fn group_by_media_kind(apps: &[Application]) -> HashMap<MediaKind, Vec<Application>> {
+	let mut groups: HashMap<MediaKind, Vec<Application>> = HashMap::new();
+	for app in apps {
+		if let Some(kind) = app.media_kind() {
+			groups.entry(kind).or_default().push(app.clone());
+		}
 	}
+	groups
+}
+
+#[test]
+fn test_group_by_media_kind() {
+	let app = |name: &str, mime_types: &[&str]| {
+		Application::create(DesktopFile {
+			name: Some(name.into()),
+			mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = [
+		app("Music", &["audio/mpeg", "audio/flac"]),
+		app("Movies", &["video/mp4", "video/x-matroska", "audio/mpeg"]),
+		app("Photos", &["image/png", "image/jpeg"]),
+		app("Podcasts", &["audio/ogg"]),
+		app("Notes", &["text/plain", "text/markdown"]),
+		app("Browser", &["x-scheme-handler/https", "text/html"]),
+		app("Settings", &[]),
+	];
+	let groups = group_by_media_kind(&apps);
+	let names = |kind: MediaKind| -> Vec<&str> {
+		groups[&kind]
+			.iter()
+			.map(|app| app.name().unwrap())
+			.collect()
+	};
+	assert_eq!(names(MediaKind::Audio), ["Music", "Podcasts"]);
+	assert_eq!(names(MediaKind::Video), ["Movies"]);
+	assert_eq!(names(MediaKind::Image), ["Photos"]);
+	assert_eq!(names(MediaKind::Text), ["Notes", "Browser"]);
+	assert_eq!(groups.values().map(Vec::len).sum::<usize>(), 6);
+}
+
+#[test]
+fn test_accessible_description() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Firefox\nGenericName=Web Browser\nGenericName[de]=Webbrowser\n\
+		Comment=Browse the Web\nComment[de]=Im Internet surfen\nExec=firefox\n",
+		"firefox.desktop",
+	)
+	.unwrap();
+	let application = Application::create(desktop_file).unwrap();
+	assert_eq!(
+		application.accessible_description(Some("en_US")),
+		"Firefox, Web Browser. Browse the Web"
+	);
+	assert_eq!(
+		application.accessible_description(Some("de_DE")),
+		"Firefox, Webbrowser. Im Internet surfen"
+	);
+
+	let bare = Application::create(DesktopFile {
+		name: Some("Calculator".into()),
+		generic_name: Some("Calculator".into()),
+		..Default::default()
+	})
+	.unwrap();
+	assert_eq!(bare.accessible_description(Some("en")), "Calculator");
+}
+
+#[test]
+fn test_preferences_action() {
+	let app = |contents: &str| {
+		Application::create(DesktopFile::from_bytes(contents.as_bytes(), "app.desktop").unwrap())
+			.unwrap()
+	};
+	let editor = app(
+		"[Desktop Entry]\nName=Editor\nExec=editor\nActions=new-window;preferences;\n\n\
+		[Desktop Action new-window]\nName=New Window\nExec=editor --new-window\n\n\
+		[Desktop Action preferences]\nName=Preferences\nExec=editor --preferences\n",
+	);
+	let action = editor.preferences_action().unwrap();
+	assert_eq!(action.id, "preferences");
+	assert_eq!(action.command.as_deref(), Some("editor --preferences"));
+
+	// Matched by name when the ID isn't conventional
+	let player = app(
+		"[Desktop Entry]\nName=Player\nExec=player\nActions=Prefs2;\n\n\
+		[Desktop Action Prefs2]\nName=Settings\nExec=player --settings\n",
+	);
+	assert_eq!(player.preferences_action().unwrap().id, "Prefs2");
+
+	let browser = app(
+		"[Desktop Entry]\nName=Browser\nExec=browser\nActions=new-window;\n\n\
+		[Desktop Action new-window]\nName=New Window\nExec=browser --new-window\n",
+	);
+	assert_eq!(browser.preferences_action(), None);
+}
+
+#[test]
+fn test_display_name() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Files\nX-GNOME-FullName=GNOME Files\n\
+		X-GNOME-FullName[de]=GNOME Dateien\nExec=nautilus\n",
+		"org.gnome.Nautilus.desktop",
+	)
+	.unwrap();
+	let application = Application::create(desktop_file).unwrap();
+	assert_eq!(application.display_name(Some("en"), false), Some("Files"));
+	assert_eq!(
+		application.display_name(Some("en"), true),
+		Some("GNOME Files")
+	);
+	assert_eq!(
+		application.display_name(Some("de_DE"), true),
+		Some("GNOME Dateien")
+	);
+
+	let short_only = Application::create(DesktopFile {
+		name: Some("Calculator".into()),
+		..Default::default()
+	})
+	.unwrap();
+	assert_eq!(short_only.display_name(None, true), Some("Calculator"));
 }
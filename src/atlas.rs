@@ -0,0 +1,236 @@
+use image::{GenericImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	io::ErrorKind,
+	path::{Path, PathBuf},
+};
+
+/// Where an icon ended up in an atlas, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlasRect {
+	/// Index of the atlas PNG in `Atlas::pages`
+	pub page: usize,
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+}
+impl AtlasRect {
+	/// `[u_min, v_min, u_max, v_max]` of the rect in a square atlas page of `page_size` pixels
+	pub fn uv(&self, page_size: u32) -> [f32; 4] {
+		let page_size = page_size as f32;
+		[
+			self.x as f32 / page_size,
+			self.y as f32 / page_size,
+			(self.x + self.width) as f32 / page_size,
+			(self.y + self.height) as f32 / page_size,
+		]
+	}
+}
+
+/// Processed icons packed into a few big PNGs, so the render layer can upload a handful of textures instead of hundreds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Atlas {
+	/// Width and height of every page
+	pub page_size: u32,
+	pub pages: Vec<PathBuf>,
+	pub rects: HashMap<String, AtlasRect>,
+}
+impl Atlas {
+	pub fn rect(&self, key: &str) -> Option<AtlasRect> {
+		self.rects.get(key).copied()
+	}
+	pub fn uv(&self, key: &str) -> Option<[f32; 4]> {
+		Some(self.rect(key)?.uv(self.page_size))
+	}
+
+	/// Saves the lookup table as `atlas.json` in `dir`, next to the pages
+	pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), std::io::Error> {
+		let json = serde_json::to_string(self).map_err(|_| ErrorKind::InvalidData)?;
+		std::fs::write(dir.as_ref().join("atlas.json"), json)
+	}
+	pub fn load(dir: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+		let json = std::fs::read_to_string(dir.as_ref().join("atlas.json"))?;
+		serde_json::from_str(&json).map_err(|_| ErrorKind::InvalidData.into())
+	}
+}
+
+/// Shelf packing: tallest first, left to right in rows, a new page once a page is full.
+/// `padding` pixels are kept between rects so sampling doesn't bleed into the neighbours.
+/// `None` for sizes that don't fit in a page at all.
+pub fn pack(sizes: &[(u32, u32)], page_size: u32, padding: u32) -> Vec<Option<AtlasRect>> {
+	let mut order: Vec<usize> = (0..sizes.len()).collect();
+	order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+	let mut rects = vec![None; sizes.len()];
+	let (mut page, mut x, mut y, mut shelf_height) = (0, 0, 0, 0);
+	for i in order {
+		let (width, height) = sizes[i];
+		if width > page_size || height > page_size {
+			continue;
+		}
+		if x + width > page_size {
+			x = 0;
+			y += shelf_height + padding;
+			shelf_height = 0;
+		}
+		if y + height > page_size {
+			page += 1;
+			x = 0;
+			y = 0;
+			shelf_height = 0;
+		}
+		rects[i] = Some(AtlasRect {
+			page,
+			x,
+			y,
+			width,
+			height,
+		});
+		x += width + padding;
+		shelf_height = shelf_height.max(height);
+	}
+	rects
+}
+
+/// Packs the PNGs at the given paths into `atlas-<n>.png` pages in `dir` and saves the lookup table with them.
+/// Icons that can't be read or don't fit in a page are left out, they can still be used as their own texture.
+pub fn build_atlas(
+	icons: impl IntoIterator<Item = (String, PathBuf)>,
+	page_size: u32,
+	dir: impl AsRef<Path>,
+) -> Result<Atlas, std::io::Error> {
+	let dir = dir.as_ref();
+	let images: Vec<(String, RgbaImage)> = icons
+		.into_iter()
+		.filter_map(|(key, path)| Some((key, image::open(path).ok()?.into_rgba8())))
+		.collect();
+	let sizes: Vec<_> = images.iter().map(|(_, image)| image.dimensions()).collect();
+	let placed = pack(&sizes, page_size, 1);
+
+	let page_count = placed.iter().flatten().map(|rect| rect.page + 1).max();
+	let mut pages = vec![RgbaImage::new(page_size, page_size); page_count.unwrap_or(0)];
+	let mut rects = HashMap::new();
+	for ((key, image), rect) in images.into_iter().zip(placed) {
+		let Some(rect) = rect else {
+			continue;
+		};
+		pages[rect.page]
+			.copy_from(&image, rect.x, rect.y)
+			.map_err(|_| ErrorKind::InvalidInput)?;
+		rects.insert(key, rect);
+	}
+
+	std::fs::create_dir_all(dir)?;
+	let pages = pages
+		.into_iter()
+		.enumerate()
+		.map(|(i, page)| {
+			let path = dir.join(format!("atlas-{}.png", i));
+			page.save_with_format(&path, image::ImageFormat::Png)
+				.map_err(|_| ErrorKind::InvalidData)?;
+			Ok(path)
+		})
+		.collect::<Result<Vec<_>, std::io::Error>>()?;
+	let atlas = Atlas {
+		page_size,
+		pages,
+		rects,
+	};
+	atlas.save(dir)?;
+	Ok(atlas)
+}
+
+#[test]
+fn test_pack() {
+	let rects = pack(&[(32, 32), (64, 64), (32, 32), (200, 10), (48, 48)], 128, 0);
+	// Tallest first: 64 and 48 on the first shelf, then the 32s, the 200 wide one never fits
+	assert_eq!(rects[3], None);
+	let rect = |i: usize| rects[i].unwrap();
+	assert_eq!((rect(1).page, rect(1).x, rect(1).y), (0, 0, 0));
+	assert_eq!((rect(4).page, rect(4).x, rect(4).y), (0, 64, 0));
+	assert_eq!((rect(0).page, rect(0).x, rect(0).y), (0, 0, 64));
+	assert_eq!((rect(2).page, rect(2).x, rect(2).y), (0, 32, 64));
+
+	// Spills over to a second page once the first is full
+	let rects = pack(&[(64, 64); 5], 128, 0);
+	assert_eq!(rects.iter().filter(|r| r.unwrap().page == 0).count(), 4);
+	assert_eq!(rects[4].unwrap().page, 1);
+	assert_eq!((rects[4].unwrap().x, rects[4].unwrap().y), (0, 0));
+
+	// No overlaps with padding
+	let rects: Vec<_> = pack(&[(30, 30); 9], 100, 2).into_iter().flatten().collect();
+	for (i, a) in rects.iter().enumerate() {
+		for b in &rects[i + 1..] {
+			assert!(
+				a.page != b.page
+					|| a.x + a.width + 2 <= b.x
+					|| b.x + b.width + 2 <= a.x
+					|| a.y + a.height + 2 <= b.y
+					|| b.y + b.height + 2 <= a.y,
+				"{:?} overlaps {:?}",
+				a,
+				b
+			);
+		}
+	}
+
+	assert_eq!(
+		AtlasRect {
+			page: 0,
+			x: 32,
+			y: 64,
+			width: 32,
+			height: 64
+		}
+		.uv(128),
+		[0.25, 0.5, 0.5, 1.0]
+	);
+}
+
+#[test]
+fn test_build_atlas() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let icon = |name: &str, size: u32, color: [u8; 4]| {
+		let path = dir.path().join(format!("{}.png", name));
+		RgbaImage::from_pixel(size, size, image::Rgba(color))
+			.save(&path)
+			.unwrap();
+		(name.to_string(), path)
+	};
+	let icons = [
+		icon("red", 32, [255, 0, 0, 255]),
+		icon("green", 16, [0, 255, 0, 255]),
+		icon("blue", 32, [0, 0, 255, 255]),
+		("missing".to_string(), dir.path().join("missing.png")),
+	];
+	let atlas_dir = dir.path().join("atlas");
+	let atlas = build_atlas(icons, 64, &atlas_dir).unwrap();
+
+	assert_eq!(atlas.pages, [atlas_dir.join("atlas-0.png")]);
+	assert_eq!(atlas.rects.len(), 3);
+	assert_eq!(atlas.rect("missing"), None);
+	let page = image::open(&atlas.pages[0]).unwrap().into_rgba8();
+	assert_eq!(page.dimensions(), (64, 64));
+	for (name, color, size) in [
+		("red", [255, 0, 0, 255], 32),
+		("green", [0, 255, 0, 255], 16),
+		("blue", [0, 0, 255, 255], 32),
+	] {
+		let rect = atlas.rect(name).unwrap();
+		assert_eq!((rect.width, rect.height), (size, size));
+		assert_eq!(page.get_pixel(rect.x, rect.y).0, color, "{}", name);
+		assert_eq!(
+			page.get_pixel(rect.x + size - 1, rect.y + size - 1).0,
+			color,
+			"{}",
+			name
+		);
+	}
+	let [u_min, v_min, u_max, v_max] = atlas.uv("green").unwrap();
+	assert_eq!(u_max - u_min, 0.25);
+	assert_eq!(v_max - v_min, 0.25);
+
+	assert_eq!(Atlas::load(&atlas_dir).unwrap(), atlas);
+}
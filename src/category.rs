@@ -0,0 +1,224 @@
+use crate::application::Application;
+use std::collections::HashMap;
+
+/// The main categories of the freedesktop menu spec
+pub const MAIN_CATEGORIES: [&str; 13] = [
+	"AudioVideo",
+	"Audio",
+	"Video",
+	"Development",
+	"Education",
+	"Game",
+	"Graphics",
+	"Network",
+	"Office",
+	"Science",
+	"Settings",
+	"System",
+	"Utility",
+];
+
+/// Bucket for applications without any main category
+pub const OTHER_CATEGORY: &str = "Other";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CategoryMatching {
+	#[default]
+	Exact,
+	/// `utility` and `UTILITY` are treated as `Utility`
+	CaseInsensitive,
+}
+
+/// Puts `category` in its canonical case when it's a main category and matching is case insensitive
+pub fn normalize_category(category: &str, matching: CategoryMatching) -> String {
+	match matching {
+		CategoryMatching::Exact => category.to_string(),
+		CategoryMatching::CaseInsensitive => MAIN_CATEGORIES
+			.iter()
+			.find(|main| main.eq_ignore_ascii_case(category))
+			.map(|main| main.to_string())
+			.unwrap_or_else(|| category.to_string()),
+	}
+}
+
+pub fn has_category(app: &Application, category: &str, matching: CategoryMatching) -> bool {
+	app.categories().iter().any(|c| match matching {
+		CategoryMatching::Exact => c == category,
+		CategoryMatching::CaseInsensitive => c.eq_ignore_ascii_case(category),
+	})
+}
+
+pub fn filter_by_category(
+	apps: &[Application],
+	category: &str,
+	matching: CategoryMatching,
+) -> Vec<Application> {
+	apps.iter()
+		.filter(|app| has_category(app, category, matching))
+		.cloned()
+		.collect()
+}
+
+/// The first main category of `app`, or `OTHER_CATEGORY`
+pub fn main_category(app: &Application, matching: CategoryMatching) -> String {
+	app.categories()
+		.iter()
+		.map(|c| normalize_category(c, matching))
+		.find(|c| MAIN_CATEGORIES.contains(&c.as_str()))
+		.unwrap_or_else(|| OTHER_CATEGORY.to_string())
+}
+
+/// Common keywords (lowercase) and the main category applications with them usually are in
+const KEYWORD_CATEGORIES: [(&str, &str); 40] = [
+	("code", "Development"),
+	("programming", "Development"),
+	("ide", "Development"),
+	("debugger", "Development"),
+	("compiler", "Development"),
+	("git", "Development"),
+	("image", "Graphics"),
+	("photo", "Graphics"),
+	("drawing", "Graphics"),
+	("paint", "Graphics"),
+	("vector", "Graphics"),
+	("music", "AudioVideo"),
+	("audio", "AudioVideo"),
+	("video", "AudioVideo"),
+	("player", "AudioVideo"),
+	("movie", "AudioVideo"),
+	("web", "Network"),
+	("browser", "Network"),
+	("internet", "Network"),
+	("email", "Network"),
+	("mail", "Network"),
+	("chat", "Network"),
+	("document", "Office"),
+	("spreadsheet", "Office"),
+	("presentation", "Office"),
+	("pdf", "Office"),
+	("game", "Game"),
+	("games", "Game"),
+	("puzzle", "Game"),
+	("math", "Education"),
+	("science", "Education"),
+	("learning", "Education"),
+	("settings", "Settings"),
+	("preferences", "Settings"),
+	("terminal", "System"),
+	("shell", "System"),
+	("monitor", "System"),
+	("calculator", "Utility"),
+	("archive", "Utility"),
+	("notes", "Utility"),
+];
+
+/// A best guess at the main category of `app` from its `Keywords`, for applications that don't declare
+/// any `Categories`. Only meant as a fallback, the first keyword with a known category wins.
+pub fn inferred_category(app: &Application) -> Option<&'static str> {
+	app.keywords().iter().find_map(|keyword| {
+		let keyword = keyword.to_lowercase();
+		KEYWORD_CATEGORIES
+			.iter()
+			.find(|(known, _)| *known == keyword)
+			.map(|(_, category)| *category)
+	})
+}
+
+/// Buckets every application under its first main category
+pub fn group_by_category(
+	apps: &[Application],
+	matching: CategoryMatching,
+) -> HashMap<String, Vec<Application>> {
+	group_by(apps, |app| main_category(app, matching))
+}
+
+/// Same as `group_by_category`, but applications without any `Categories` go under the
+/// `inferred_category` of their keywords instead of under `OTHER_CATEGORY` when there's one
+pub fn group_by_category_inferred(
+	apps: &[Application],
+	matching: CategoryMatching,
+) -> HashMap<String, Vec<Application>> {
+	group_by(apps, |app| match inferred_category(app) {
+		Some(category) if app.categories().is_empty() => category.to_string(),
+		_ => main_category(app, matching),
+	})
+}
+
+fn group_by(
+	apps: &[Application],
+	category: impl Fn(&Application) -> String,
+) -> HashMap<String, Vec<Application>> {
+	let mut groups: HashMap<String, Vec<Application>> = HashMap::new();
+	for app in apps {
+		groups.entry(category(app)).or_default().push(app.clone());
+	}
+	groups
+}
+
+#[test]
+fn test_group_by_category_case_insensitive() {
+	let test_app = |name: &str, categories: &[&str]| {
+		Application::create(crate::xdg::DesktopFile {
+			name: Some(name.to_string()),
+			categories: categories.iter().map(|c| c.to_string()).collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = vec![
+		test_app("Calculator", &["Utility"]),
+		test_app("Archiver", &["utility", "Archiving"]),
+		test_app("Mystery", &[]),
+	];
+
+	let exact = group_by_category(&apps, CategoryMatching::Exact);
+	assert_eq!(exact["Utility"].len(), 1);
+	assert_eq!(exact[OTHER_CATEGORY].len(), 2);
+
+	let lenient = group_by_category(&apps, CategoryMatching::CaseInsensitive);
+	assert_eq!(lenient["Utility"].len(), 2);
+	assert_eq!(lenient[OTHER_CATEGORY].len(), 1);
+	assert_eq!(
+		filter_by_category(&apps, "UTILITY", CategoryMatching::CaseInsensitive).len(),
+		2
+	);
+}
+
+#[test]
+fn test_group_by_category_inferred() {
+	let test_app = |name: &str, categories: &[&str], keywords: &[&str]| {
+		Application::create(crate::xdg::DesktopFile {
+			name: Some(name.to_string()),
+			categories: categories.iter().map(|c| c.to_string()).collect(),
+			keywords: keywords.iter().map(|k| k.to_string()).collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let apps = vec![
+		test_app("Editor", &[], &["Text", "Code", "Image"]),
+		test_app("Sketch", &[], &["Paint"]),
+		test_app("Declared", &["Office"], &["Game"]),
+		test_app("Unknown", &["X-Custom"], &["Music"]),
+		test_app("Mystery", &[], &["Thing"]),
+	];
+	assert_eq!(inferred_category(&apps[0]), Some("Development"));
+	assert_eq!(inferred_category(&apps[4]), None);
+
+	let groups = group_by_category_inferred(&apps, CategoryMatching::Exact);
+	let names = |category: &str| -> Vec<&str> {
+		groups[category]
+			.iter()
+			.map(|app| app.name().unwrap())
+			.collect()
+	};
+	assert_eq!(names("Development"), ["Editor"]);
+	assert_eq!(names("Graphics"), ["Sketch"]);
+	// Declared categories always win, even ones that aren't main categories
+	assert_eq!(names("Office"), ["Declared"]);
+	assert_eq!(names(OTHER_CATEGORY), ["Unknown", "Mystery"]);
+	assert_eq!(
+		group_by_category(&apps, CategoryMatching::Exact)[OTHER_CATEGORY].len(),
+		4
+	);
+}
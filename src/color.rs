@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorError {
+	/// A component isn't in the 0-1 range, eg: it's a 0-255 value
+	OutOfRange(f32),
+}
+impl Display for ColorError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ColorError::OutOfRange(c) => write!(
+				f,
+				"color component {c} is not in the 0-1 range (0-255 values need to be divided by 255)"
+			),
+		}
+	}
+}
+impl std::error::Error for ColorError {}
+
+/// A linear RGBA color with components in the 0-1 range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "[f32; 4]", into = "[f32; 4]")]
+pub struct Rgba {
+	pub r: f32,
+	pub g: f32,
+	pub b: f32,
+	pub a: f32,
+}
+impl Rgba {
+	pub const BLACK: Rgba = Rgba::new(0.0, 0.0, 0.0, 1.0);
+	pub const WHITE: Rgba = Rgba::new(1.0, 1.0, 1.0, 1.0);
+
+	/// Unchecked, for constants
+	pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+		Rgba { r, g, b, a }
+	}
+	/// Errors if any component is outside of the 0-1 range or NaN
+	pub fn try_new(r: f32, g: f32, b: f32, a: f32) -> Result<Self, ColorError> {
+		for c in [r, g, b, a] {
+			if !(0.0..=1.0).contains(&c) {
+				return Err(ColorError::OutOfRange(c));
+			}
+		}
+		Ok(Rgba { r, g, b, a })
+	}
+	/// Clamps every component to the 0-1 range, NaN becomes 0
+	pub fn clamped(r: f32, g: f32, b: f32, a: f32) -> Self {
+		let clamp = |c: f32| if c.is_nan() { 0.0 } else { c.clamp(0.0, 1.0) };
+		Rgba::new(clamp(r), clamp(g), clamp(b), clamp(a))
+	}
+	/// `hue` in degrees, `saturation` and `lightness` in the 0-1 range
+	pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+		let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+		let hue = hue.rem_euclid(360.0) / 60.0;
+		let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+		let (r, g, b) = match hue as u8 {
+			0 => (chroma, x, 0.0),
+			1 => (x, chroma, 0.0),
+			2 => (0.0, chroma, x),
+			3 => (0.0, x, chroma),
+			4 => (x, 0.0, chroma),
+			_ => (chroma, 0.0, x),
+		};
+		let m = lightness - chroma / 2.0;
+		Rgba::clamped(r + m, g + m, b + m, 1.0)
+	}
+	/// A color that only depends on `key`, with the same saturation and lightness for every key
+	pub fn hashed(key: &str) -> Self {
+		// FNV-1a, unlike `DefaultHasher` it's guaranteed to stay the same between releases
+		let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+			(hash ^ byte as u64).wrapping_mul(0x100000001b3)
+		});
+		Rgba::from_hsl((hash % 360) as f32, 0.6, 0.5)
+	}
+	pub fn to_array(self) -> [f32; 4] {
+		[self.r, self.g, self.b, self.a]
+	}
+	pub fn relative_luminance(self) -> f32 {
+		0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+	}
+	/// Black or white, whichever is more readable on top of this color
+	pub fn contrasting_text_color(self) -> Rgba {
+		// Where the contrast ratio against black and white is the same
+		if self.relative_luminance() > 0.179 {
+			Rgba::BLACK
+		} else {
+			Rgba::WHITE
+		}
+	}
+}
+impl TryFrom<[f32; 4]> for Rgba {
+	type Error = ColorError;
+	fn try_from([r, g, b, a]: [f32; 4]) -> Result<Self, Self::Error> {
+		Rgba::try_new(r, g, b, a)
+	}
+}
+impl From<Rgba> for [f32; 4] {
+	fn from(color: Rgba) -> Self {
+		color.to_array()
+	}
+}
+
+#[test]
+fn test_rgba_validation() {
+	assert_eq!(
+		Rgba::try_new(0.0, 1.0, 1.0, 0.5),
+		Ok(Rgba::new(0.0, 1.0, 1.0, 0.5))
+	);
+	assert_eq!(
+		Rgba::try_new(0.0, 255.0, 255.0, 1.0),
+		Err(ColorError::OutOfRange(255.0))
+	);
+	assert!(Rgba::try_new(f32::NAN, 0.0, 0.0, 1.0).is_err());
+	assert_eq!(
+		Rgba::clamped(-0.5, 2.0, 0.5, 1.0),
+		Rgba::new(0.0, 1.0, 0.5, 1.0)
+	);
+	assert!(serde_json::from_str::<Rgba>("[0.0, 255.0, 0.0, 1.0]").is_err());
+	assert_eq!(
+		serde_json::from_str::<Rgba>("[0.0, 1.0, 0.0, 0.5]").unwrap(),
+		Rgba::new(0.0, 1.0, 0.0, 0.5)
+	);
+}
+
+#[test]
+fn test_contrasting_text_color() {
+	assert_eq!(
+		Rgba::new(0.9, 0.9, 0.6, 1.0).contrasting_text_color(),
+		Rgba::BLACK
+	);
+	assert_eq!(
+		Rgba::new(0.05, 0.0, 0.3, 1.0).contrasting_text_color(),
+		Rgba::WHITE
+	);
+}
+
+#[test]
+fn test_hashed_color() {
+	assert_eq!(Rgba::from_hsl(0.0, 1.0, 0.5), Rgba::new(1.0, 0.0, 0.0, 1.0));
+	assert_eq!(
+		Rgba::from_hsl(240.0, 1.0, 0.5),
+		Rgba::new(0.0, 0.0, 1.0, 1.0)
+	);
+	assert_eq!(
+		Rgba::hashed("firefox.desktop"),
+		Rgba::hashed("firefox.desktop")
+	);
+	assert_ne!(
+		Rgba::hashed("firefox.desktop"),
+		Rgba::hashed("org.gnome.Nautilus.desktop")
+	);
+}
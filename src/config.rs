@@ -0,0 +1,41 @@
+use std::{
+	collections::HashSet,
+	fs,
+	path::{Path, PathBuf},
+};
+
+pub fn get_config_dir() -> PathBuf {
+	dirs::config_dir()
+		.unwrap_or(dirs::home_dir().unwrap().join(".config"))
+		.join("protostar")
+}
+
+/// Reads a list of desktop IDs (eg: `firefox.desktop`), one per line.
+/// Empty lines and lines starting with "#" are ignored, a missing file is an empty list.
+pub fn read_id_list(path: impl AsRef<Path>) -> HashSet<String> {
+	let Ok(contents) = fs::read_to_string(path) else { return HashSet::new(); };
+	contents
+		.lines()
+		.map(|line| line.trim())
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| line.to_string())
+		.collect()
+}
+
+/// Desktop IDs the user never wants to see in a launcher, read from `$XDG_CONFIG_HOME/protostar/blocklist`
+pub fn load_blocklist() -> HashSet<String> {
+	read_id_list(get_config_dir().join("blocklist"))
+}
+
+#[test]
+fn test_read_id_list() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let file = dir.path().join("blocklist");
+	fs::write(&file, "# uninstallers\nfoo.desktop\n\n  bar.desktop  \n").unwrap();
+
+	let ids = read_id_list(&file);
+	assert_eq!(ids.len(), 2);
+	assert!(ids.contains("foo.desktop"));
+	assert!(ids.contains("bar.desktop"));
+	assert!(read_id_list(dir.path().join("missing")).is_empty());
+}
@@ -0,0 +1,727 @@
+use std::{
+	env,
+	ffi::OsStr,
+	os::unix::fs::PermissionsExt,
+	path::{Path, PathBuf},
+};
+
+/// Splits the value of an `Exec` key into its arguments.
+/// Follows the desktop entry spec quoting rules, plus single quotes since a lot of files use them anyway.
+pub fn tokenize_exec(exec: &str) -> Vec<String> {
+	let mut args = Vec::new();
+	let mut current = String::new();
+	let mut in_arg = false;
+	let mut chars = exec.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			' ' | '\t' => {
+				if in_arg {
+					args.push(std::mem::take(&mut current));
+					in_arg = false;
+				}
+			}
+			'"' => {
+				in_arg = true;
+				while let Some(c) = chars.next() {
+					match c {
+						'"' => break,
+						'\\' => match chars.next() {
+							Some(escaped @ ('"' | '`' | '$' | '\\')) => current.push(escaped),
+							Some(other) => {
+								current.push('\\');
+								current.push(other);
+							}
+							None => current.push('\\'),
+						},
+						_ => current.push(c),
+					}
+				}
+			}
+			'\'' => {
+				in_arg = true;
+				for c in chars.by_ref() {
+					if c == '\'' {
+						break;
+					}
+					current.push(c);
+				}
+			}
+			'\\' => {
+				in_arg = true;
+				if let Some(escaped) = chars.next() {
+					current.push(escaped);
+				}
+			}
+			_ => {
+				in_arg = true;
+				current.push(c);
+			}
+		}
+	}
+	if in_arg {
+		args.push(current);
+	}
+	args
+}
+
+#[test]
+fn test_tokenize_exec() {
+	assert_eq!(tokenize_exec("firefox %u"), vec!["firefox", "%u"]);
+	assert_eq!(
+		tokenize_exec(r#""/opt/My App/app" --title "say \"hi\"" 'a b'"#),
+		vec!["/opt/My App/app", "--title", "say \"hi\"", "a b"]
+	);
+	assert_eq!(tokenize_exec("  spaced   out  "), vec!["spaced", "out"]);
+	assert_eq!(tokenize_exec(r#"empty """#), vec!["empty", ""]);
+	assert!(tokenize_exec("").is_empty());
+}
+
+/// Whether `exec` relies on the shell, eg: pipes, `&&`, redirections, globs, command substitution
+/// or variables other than `EXPANDABLE_ENV_VARS`, so it can't be run as a plain argv.
+/// Quoting follows `tokenize_exec`, metacharacters inside quotes are just text.
+pub fn needs_shell(exec: &str) -> bool {
+	let mut chars = exec.chars().peekable();
+	let mut word_start = true;
+	// Only the first word can be a variable assignment, eg: `FOO=1 app`
+	let mut first_word = String::new();
+	let mut in_first_word = true;
+	while let Some(c) = chars.next() {
+		let at_word_start = std::mem::replace(&mut word_start, false);
+		match c {
+			' ' | '\t' => {
+				word_start = true;
+				if !first_word.is_empty() {
+					in_first_word = false;
+				}
+				continue;
+			}
+			'|' | '&' | ';' | '<' | '>' | '(' | ')' | '`' | '\n' | '*' | '?' | '[' => return true,
+			'~' | '#' if at_word_start => return true,
+			'=' if in_first_word && is_env_name(&first_word) => return true,
+			'$' if needs_shell_expansion(&mut chars) => return true,
+			'\\' => {
+				chars.next();
+			}
+			'\'' => {
+				for c in chars.by_ref() {
+					if c == '\'' {
+						break;
+					}
+				}
+			}
+			'"' => {
+				while let Some(c) = chars.next() {
+					match c {
+						'"' => break,
+						'`' => return true,
+						'$' if needs_shell_expansion(&mut chars) => return true,
+						'\\' => {
+							chars.next();
+						}
+						_ => (),
+					}
+				}
+			}
+			_ => (),
+		}
+		if in_first_word {
+			first_word.push(c);
+		}
+	}
+	false
+}
+
+/// Whether the `$` just read starts something only a shell can expand, ie: anything but a
+/// reference `expand_env_refs` takes care of
+fn needs_shell_expansion(chars: &mut std::iter::Peekable<std::str::Chars>) -> bool {
+	let name: String = match chars.peek() {
+		Some('{') => {
+			chars.next();
+			chars.by_ref().take_while(|&c| c != '}').collect()
+		}
+		Some(c) if c.is_ascii_alphanumeric() || *c == '_' => {
+			let mut name = String::new();
+			while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+				name.push(c);
+			}
+			name
+		}
+		// A lone `$` is just a dollar sign, `$(`, `$?`... are caught here
+		Some(' ' | '\t' | '"') | None => return false,
+		Some(_) => return true,
+	};
+	!EXPANDABLE_ENV_VARS.contains(&name.as_str())
+}
+
+fn is_env_name(word: &str) -> bool {
+	word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+		&& word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[test]
+fn test_needs_shell() {
+	for exec in [
+		"foo --bar",
+		"foo %U",
+		"/opt/app/run --name=x",
+		"$HOME/bin/app ${XDG_DATA_HOME}/file",
+		r#"app --title "a | b && c" 'x > y; $(z)'"#,
+		r#"app escaped\&\& 100$"#,
+		"env FOO=1 app",
+		"app a~b c#d",
+		r#"sh -c "echo hi""#,
+	] {
+		assert!(!needs_shell(exec), "{} shouldn't need a shell", exec);
+	}
+	for exec in [
+		"foo && bar",
+		"foo | bar",
+		"foo; bar",
+		"foo > log",
+		"foo 2>&1",
+		"foo &",
+		"app $(cat config)",
+		"app `cat config`",
+		r#"app "$(cat config)""#,
+		"app $DISPLAY",
+		"app ${HOME:-/tmp}",
+		"app $?",
+		"app *.txt",
+		"app ~/file",
+		"FOO=1 app",
+		"app # comment",
+		"app\nother",
+	] {
+		assert!(needs_shell(exec), "{} should need a shell", exec);
+	}
+}
+
+/// What a field code (eg: `%U`) of an `Exec` key stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldCode {
+	/// `%f`, `%F`, `%u` and `%U`, the files or URLs being opened
+	Files,
+	/// `%i`, `%c` and `%k`, the icon, the name and the location of the desktop file
+	Entry,
+	/// `%d`, `%D`, `%n`, `%N`, `%v` and `%m`, deprecated, the spec says to drop them
+	Deprecated,
+}
+impl FieldCode {
+	pub fn from_char(c: char) -> Option<Self> {
+		match c {
+			'f' | 'F' | 'u' | 'U' => Some(FieldCode::Files),
+			'i' | 'c' | 'k' => Some(FieldCode::Entry),
+			'd' | 'D' | 'n' | 'N' | 'v' | 'm' => Some(FieldCode::Deprecated),
+			_ => None,
+		}
+	}
+}
+
+/// Removes the field codes of an argument of `Exec`, since protostar launches without files and
+/// doesn't fill in the others. `%%` becomes `%` and unknown codes are kept as they are.
+/// `None` if the argument was only field codes, so it has to be dropped rather than passed empty.
+pub fn strip_field_codes(arg: &str) -> Option<String> {
+	substitute_field_codes(arg, &[]).pop()
+}
+
+#[test]
+fn test_strip_field_codes() {
+	let stripped = |exec: &str| -> Vec<String> {
+		tokenize_exec(exec)
+			.iter()
+			.filter_map(|arg| strip_field_codes(arg))
+			.collect()
+	};
+	for code in "fFuUdDnNickvm".chars() {
+		assert_eq!(
+			stripped(&format!("app --flag %{} last", code)),
+			["app", "--flag", "last"],
+			"%{} left something behind",
+			code
+		);
+	}
+	assert_eq!(FieldCode::from_char('d'), Some(FieldCode::Deprecated));
+	assert_eq!(FieldCode::from_char('N'), Some(FieldCode::Deprecated));
+	assert_eq!(FieldCode::from_char('U'), Some(FieldCode::Files));
+	assert_eq!(FieldCode::from_char('x'), None);
+
+	assert_eq!(stripped("app %d%n %U"), ["app"]);
+	assert_eq!(
+		stripped("app --dir=%d --name=%c"),
+		["app", "--dir=", "--name="]
+	);
+	assert_eq!(stripped("app 100%% %x"), ["app", "100%", "%x"]);
+	// Explicitly empty arguments aren't field codes
+	assert_eq!(stripped(r#"app "" %f"#), ["app", ""]);
+
+	// Commands run through a shell lose the same arguments, quoted or not
+	let _env = crate::test_utils::lock_env();
+	for code in "fFuUdDnNickvm".chars() {
+		for exec in [
+			format!("printf '%s|' %{} last", code),
+			format!(r#"printf '%s|' "%{}" last"#, code),
+			format!("printf '%s|' '%{}' last", code),
+			format!(r#"printf '%s|' "--opt=%{}" last"#, code),
+		] {
+			let script = substitute_field_codes_for_shell(&exec, &[]);
+			let output = std::process::Command::new("sh")
+				.args(["-c", &script])
+				.output()
+				.unwrap();
+			let expected = stripped(&exec)[2..].join("|") + "|";
+			assert_eq!(
+				String::from_utf8_lossy(&output.stdout),
+				expected,
+				"{}",
+				script
+			);
+		}
+	}
+}
+
+/// What a field code standing for the files being opened is replaced with: the first of them for
+/// `%f` and `%u`, all of them for `%F` and `%U`
+fn field_code_files(code: char, files: &[String]) -> &[String] {
+	match code.is_lowercase() {
+		true => &files[..files.len().min(1)],
+		false => files,
+	}
+}
+
+/// Fills the field codes of an argument of `Exec` in with `files`, the paths or URLs being opened.
+/// `%F` and `%U` give an argument per file when they are the whole argument, the other codes are
+/// removed like `strip_field_codes` does. The arguments are the ones `tokenize_exec` gives, so
+/// codes that were quoted (eg: `"%f"`) get filled in too, as the spec says.
+pub fn substitute_field_codes(arg: &str, files: &[String]) -> Vec<String> {
+	if arg == "%F" || arg == "%U" {
+		return files.to_vec();
+	}
+	let mut substituted = String::with_capacity(arg.len());
+	let mut removed = false;
+	let mut chars = arg.chars().peekable();
+	while let Some(c) = chars.next() {
+		if c != '%' {
+			substituted.push(c);
+			continue;
+		}
+		match chars.peek().copied() {
+			Some('%') => {
+				chars.next();
+				substituted.push('%');
+			}
+			Some(code) => match FieldCode::from_char(code) {
+				Some(FieldCode::Files) => {
+					chars.next();
+					let value = field_code_files(code, files).join(" ");
+					removed |= value.is_empty();
+					substituted.push_str(&value);
+				}
+				Some(_) => {
+					chars.next();
+					removed = true;
+				}
+				None => substituted.push('%'),
+			},
+			None => substituted.push('%'),
+		}
+	}
+	match removed && substituted.is_empty() {
+		true => Vec::new(),
+		false => vec![substituted],
+	}
+}
+
+/// Quotes `arg` so `sh` reads it back as is
+pub fn shell_quote(arg: &str) -> String {
+	format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Same as `substitute_field_codes`, but on a whole `Exec` value that's run with `sh -c`.
+/// Every file gets quoted to suit where its code is (eg: inside double quotes), so the shell
+/// passes it on as is, whatever it contains.
+pub fn substitute_field_codes_for_shell(exec: &str, files: &[String]) -> String {
+	#[derive(PartialEq)]
+	enum Quote {
+		None,
+		Single,
+		Double,
+	}
+	let mut substituted = String::with_capacity(exec.len());
+	let mut quote = Quote::None;
+	// Where the open quotes start, and whether a field code inside them substituted to nothing
+	let mut quote_start = 0;
+	let mut removed_in_quote = false;
+	let mut chars = exec.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' if quote != Quote::Single => {
+				substituted.push(c);
+				if let Some(escaped) = chars.next() {
+					substituted.push(escaped);
+				}
+				continue;
+			}
+			'\'' | '"' if quote == Quote::None => {
+				quote = match c {
+					'\'' => Quote::Single,
+					_ => Quote::Double,
+				};
+				quote_start = substituted.len();
+				removed_in_quote = false;
+			}
+			'\'' | '"'
+				if (c == '\'' && quote == Quote::Single)
+					|| (c == '"' && quote == Quote::Double) =>
+			{
+				quote = Quote::None;
+				// Quotes around nothing but empty field codes would leave an empty argument
+				if removed_in_quote && substituted.len() == quote_start + 1 {
+					substituted.truncate(quote_start);
+					continue;
+				}
+			}
+			'%' => {
+				match chars.peek().copied() {
+					Some('%') => {
+						chars.next();
+						substituted.push('%');
+					}
+					Some(code) => match FieldCode::from_char(code) {
+						Some(FieldCode::Files) => {
+							chars.next();
+							let files = field_code_files(code, files);
+							if files.is_empty() {
+								removed_in_quote = true;
+								continue;
+							}
+							match quote {
+								Quote::None => substituted.push_str(
+									&files
+										.iter()
+										.map(|file| shell_quote(file))
+										.collect::<Vec<_>>()
+										.join(" "),
+								),
+								// Closes the quotes around the file and opens them again after it
+								Quote::Single => {
+									substituted.push('\'');
+									substituted.push_str(&shell_quote(&files.join(" ")));
+									substituted.push('\'');
+								}
+								Quote::Double => {
+									for c in files.join(" ").chars() {
+										if matches!(c, '"' | '\\' | '$' | '`') {
+											substituted.push('\\');
+										}
+										substituted.push(c);
+									}
+								}
+							}
+						}
+						Some(_) => {
+							chars.next();
+							removed_in_quote = true;
+						}
+						None => substituted.push('%'),
+					},
+					None => substituted.push('%'),
+				}
+				continue;
+			}
+			_ => (),
+		}
+		substituted.push(c);
+	}
+	substituted
+}
+
+#[test]
+fn test_substitute_field_codes() {
+	let _env = crate::test_utils::lock_env();
+	let files = ["/music/My Song.ogg".to_string(), "/music/b.ogg".to_string()];
+	let substituted = |exec: &str, files: &[String]| -> Vec<String> {
+		tokenize_exec(exec)
+			.iter()
+			.flat_map(|arg| substitute_field_codes(arg, files))
+			.collect()
+	};
+	assert_eq!(
+		substituted(r#"player "%f""#, &files),
+		["player", "/music/My Song.ogg"]
+	);
+	assert_eq!(
+		substituted("player %F --end", &files),
+		["player", "/music/My Song.ogg", "/music/b.ogg", "--end"]
+	);
+	assert_eq!(
+		substituted("player '--open=%u' %i %c", &files),
+		["player", "--open=/music/My Song.ogg"]
+	);
+	assert_eq!(substituted(r#"player "%U" 100%%"#, &[]), ["player", "100%"]);
+
+	// Through a real shell, quoted or not, files come out as they went in
+	let tricky = [r#"it's "$HOME" `ls` \ a\b.ogg"#.to_string()];
+	for exec in [
+		"printf '%s|' %f",
+		r#"printf '%s|' "%f""#,
+		"printf '%s|' '%f'",
+		r#"printf '%s|' "--file=%f" x%%"#,
+	] {
+		let script = substitute_field_codes_for_shell(exec, &tricky);
+		let output = std::process::Command::new("sh")
+			.args(["-c", &script])
+			.output()
+			.unwrap();
+		let expected = substituted(exec, &tricky)[2..].join("|") + "|";
+		assert_eq!(
+			String::from_utf8_lossy(&output.stdout),
+			expected,
+			"{}",
+			script
+		);
+	}
+	assert_eq!(
+		substitute_field_codes_for_shell("player %F", &files),
+		"player '/music/My Song.ogg' '/music/b.ogg'"
+	);
+	assert_eq!(
+		substitute_field_codes_for_shell("player %U %k", &[]),
+		"player  "
+	);
+	// Quoted codes without files leave no empty argument behind
+	assert_eq!(
+		substitute_field_codes_for_shell(r#"player "%f""#, &[]),
+		"player "
+	);
+	assert_eq!(
+		substitute_field_codes_for_shell("player '%f'", &[]),
+		"player "
+	);
+	assert_eq!(
+		substitute_field_codes_for_shell(r#"player "%f%U" '%k'"#, &[]),
+		"player  "
+	);
+	assert_eq!(
+		substitute_field_codes_for_shell(r#"player "--file=%f" "" '%%'"#, &[]),
+		r#"player "--file=" "" '%'"#
+	);
+}
+
+pub fn is_executable(path: impl AsRef<Path>) -> bool {
+	path.as_ref()
+		.metadata()
+		.map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+		.unwrap_or(false)
+}
+
+/// Looks up `program` in `$PATH` like a shell would
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+	find_in_dirs(program, &env::var_os("PATH")?)
+}
+
+/// Where `program`, as written in an `Exec` or `TryExec` key, is. Either an absolute path to an
+/// executable (eg: a script) or a name looked up in `$PATH`.
+pub fn resolve_program(program: &str) -> Option<PathBuf> {
+	if Path::new(program).is_absolute() {
+		is_executable(program).then(|| PathBuf::from(program))
+	} else if program.contains('/') {
+		None
+	} else {
+		find_in_path(program)
+	}
+}
+
+fn find_in_dirs(program: &str, path: &OsStr) -> Option<PathBuf> {
+	env::split_paths(path)
+		.map(|dir| dir.join(program))
+		.find(|candidate| is_executable(candidate))
+}
+
+/// Environment variables `expand_env_refs` expands, anything else is left as is
+pub const EXPANDABLE_ENV_VARS: [&str; 6] = [
+	"HOME",
+	"USER",
+	"XDG_CACHE_HOME",
+	"XDG_CONFIG_HOME",
+	"XDG_DATA_HOME",
+	"XDG_RUNTIME_DIR",
+];
+
+/// Expands `$VAR` and `${VAR}` references to the variables in `EXPANDABLE_ENV_VARS`, for launches
+/// that don't go through a shell. Other references and unset variables are left untouched.
+pub fn expand_env_refs(arg: &str) -> String {
+	expand_env_refs_with(arg, |var| env::var(var).ok())
+}
+
+fn expand_env_refs_with(arg: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+	let mut expanded = String::with_capacity(arg.len());
+	let mut rest = arg;
+	while let Some(dollar) = rest.find('$') {
+		expanded.push_str(&rest[..dollar]);
+		let after = &rest[dollar + 1..];
+		// The name of the variable and how much of `after` the reference takes
+		let (name, len) = match after.strip_prefix('{') {
+			Some(braced) => match braced.find('}') {
+				Some(end) => (&braced[..end], end + 2),
+				None => ("", 0),
+			},
+			None => {
+				let end = after
+					.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+					.unwrap_or(after.len());
+				(&after[..end], end)
+			}
+		};
+		match EXPANDABLE_ENV_VARS
+			.contains(&name)
+			.then(|| lookup(name))
+			.flatten()
+		{
+			Some(value) => {
+				expanded.push_str(&value);
+				rest = &after[len..];
+			}
+			None => {
+				expanded.push('$');
+				rest = after;
+			}
+		}
+	}
+	expanded.push_str(rest);
+	expanded
+}
+
+#[test]
+fn test_expand_env_refs() {
+	let lookup = |var: &str| match var {
+		"HOME" => Some("/home/me".to_string()),
+		"SECRET" => Some("leaked".to_string()),
+		_ => None,
+	};
+	let expand = |arg: &str| expand_env_refs_with(arg, lookup);
+	assert_eq!(expand("$HOME/bin/app"), "/home/me/bin/app");
+	assert_eq!(expand("${HOME}/.local"), "/home/me/.local");
+	assert_eq!(
+		expand("--config=$HOME/a:$HOME/b"),
+		"--config=/home/me/a:/home/me/b"
+	);
+	// Not in the whitelist, unset or not a reference at all
+	assert_eq!(expand("$SECRET"), "$SECRET");
+	assert_eq!(expand("$XDG_RUNTIME_DIR/socket"), "$XDG_RUNTIME_DIR/socket");
+	assert_eq!(expand("costs $5 or ${HOME"), "costs $5 or ${HOME");
+	assert_eq!(expand("$"), "$");
+	assert_eq!(expand("$HOMEWORK"), "$HOMEWORK");
+}
+
+/// Terminal emulators tried in order when `$TERMINAL` isn't set
+const KNOWN_TERMINALS: [&str; 9] = [
+	"x-terminal-emulator",
+	"kitty",
+	"alacritty",
+	"foot",
+	"wezterm",
+	"gnome-terminal",
+	"konsole",
+	"xfce4-terminal",
+	"xterm",
+];
+
+/// The terminal emulator to run `Terminal=true` applications in, `$TERMINAL` if it's set
+pub fn find_terminal() -> Option<PathBuf> {
+	find_terminal_in(env::var("TERMINAL").ok().as_deref(), &env::var_os("PATH")?)
+		.map(|(_, path)| path)
+}
+
+/// What to put before a command to run it in the terminal emulator `find_terminal` picks,
+/// eg: `["foot", "-e"]`
+pub fn resolve_terminal() -> Option<Vec<String>> {
+	resolve_terminal_in(env::var("TERMINAL").ok().as_deref(), &env::var_os("PATH")?)
+}
+
+fn resolve_terminal_in(terminal: Option<&str>, path: &OsStr) -> Option<Vec<String>> {
+	let (terminal, _) = find_terminal_in(terminal, path)?;
+	let mut prefix = vec![terminal.to_string()];
+	prefix.extend(
+		terminal_exec_args(terminal)
+			.iter()
+			.map(|arg| arg.to_string()),
+	);
+	Some(prefix)
+}
+
+/// Arguments after which a terminal emulator takes the command to run, `-e` for most of them
+fn terminal_exec_args(terminal: &str) -> &'static [&'static str] {
+	let name = Path::new(terminal)
+		.file_name()
+		.and_then(|name| name.to_str())
+		.unwrap_or(terminal);
+	match name {
+		"kitty" => &[],
+		"wezterm" => &["start", "--"],
+		"gnome-terminal" => &["--"],
+		"xfce4-terminal" => &["-x"],
+		_ => &["-e"],
+	}
+}
+
+/// The first terminal emulator that exists, as it was named and where it is
+fn find_terminal_in<'a>(terminal: Option<&'a str>, path: &OsStr) -> Option<(&'a str, PathBuf)> {
+	terminal
+		.into_iter()
+		.chain(KNOWN_TERMINALS)
+		.find_map(|terminal| {
+			let found = if Path::new(terminal).is_absolute() {
+				is_executable(terminal).then(|| PathBuf::from(terminal))
+			} else {
+				find_in_dirs(terminal, path)
+			};
+			found.map(|found| (terminal, found))
+		})
+}
+
+#[test]
+fn test_find_in_path() {
+	let _env = crate::test_utils::lock_env();
+	let sh = find_in_path("sh").unwrap();
+	assert!(sh.is_absolute());
+	assert!(find_in_path("surely-not-a-real-program").is_none());
+}
+
+#[test]
+fn test_find_terminal() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let path = dir.path().as_os_str();
+	assert!(find_terminal_in(None, path).is_none());
+	assert!(find_terminal_in(Some("foot"), path).is_none());
+
+	let foot = dir.path().join("foot");
+	std::fs::write(&foot, "#!/bin/sh\n").unwrap();
+	std::fs::set_permissions(&foot, std::fs::Permissions::from_mode(0o755)).unwrap();
+	assert_eq!(find_terminal_in(None, path), Some(("foot", foot.clone())));
+	assert_eq!(find_terminal_in(Some("foot"), path), Some(("foot", foot)));
+}
+
+#[test]
+fn test_resolve_terminal() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let path = dir.path().as_os_str();
+	assert!(resolve_terminal_in(None, path).is_none());
+
+	let add_executable = |name: &str| {
+		let executable = dir.path().join(name);
+		std::fs::write(&executable, "#!/bin/sh\n").unwrap();
+		std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+		executable
+	};
+	add_executable("xterm");
+	assert_eq!(resolve_terminal_in(None, path).unwrap(), ["xterm", "-e"]);
+
+	add_executable("wezterm");
+	assert_eq!(
+		resolve_terminal_in(Some("wezterm"), path).unwrap(),
+		["wezterm", "start", "--"]
+	);
+	let foot = add_executable("foot");
+	let foot = foot.to_str().unwrap();
+	assert_eq!(resolve_terminal_in(Some(foot), path).unwrap(), [foot, "-e"]);
+}
@@ -0,0 +1,401 @@
+use glam::{Mat3, Quat, Vec3};
+use mint::{Quaternion, Vector3};
+use std::f32::consts::PI;
+
+/// Decides where each of a set of tiles goes, relative to their parent
+pub trait Layout {
+	fn positions(&self, count: usize) -> Vec<Vector3<f32>>;
+}
+
+/// Rows of `columns` tiles, `spacing` apart
+#[derive(Debug, Clone)]
+pub struct GridLayout {
+	pub columns: usize,
+	pub spacing: f32,
+}
+impl Layout for GridLayout {
+	fn positions(&self, count: usize) -> Vec<Vector3<f32>> {
+		let columns = self.columns.max(1);
+		(0..count)
+			.map(|i| {
+				Vector3::from([
+					(i % columns) as f32 * self.spacing,
+					(i / columns) as f32 * self.spacing,
+					0.0,
+				])
+			})
+			.collect()
+	}
+}
+
+#[test]
+fn test_grid_layout() {
+	let layout = GridLayout {
+		columns: 2,
+		spacing: 0.1,
+	};
+	let positions = layout.positions(3);
+	assert_eq!(positions.len(), 3);
+	assert_eq!(positions[1], Vector3::from([0.1, 0.0, 0.0]));
+	assert_eq!(positions[2], Vector3::from([0.0, 0.1, 0.0]));
+}
+
+#[derive(Clone)]
+struct Hex {
+	q: isize,
+	r: isize,
+	s: isize,
+}
+
+const HEX_CENTER: Hex = Hex { q: 0, r: 0, s: 0 };
+const HEX_DIRECTION_VECTORS: [Hex; 6] = [
+	Hex { q: 1, r: 0, s: -1 },
+	Hex { q: 1, r: -1, s: 0 },
+	Hex { q: 0, r: -1, s: 1 },
+	Hex { q: -1, r: 0, s: 1 },
+	Hex { q: -1, r: 1, s: 0 },
+	Hex { q: 0, r: 1, s: -1 },
+];
+
+impl Hex {
+	fn new(q: isize, r: isize, s: isize) -> Self {
+		Hex { q, r, s }
+	}
+
+	fn get_coords(&self, size: f32) -> Vector3<f32> {
+		let x = 3.0 / 2.0 * size / 2.0 * (-self.q - self.s) as f32;
+		let y = 3.0_f32.sqrt() * size / 2.0 * ((-self.q - self.s) as f32 / 2.0 + self.s as f32);
+		Vector3::from([x, y, 0.0])
+	}
+
+	fn add(self, vec: &Hex) -> Self {
+		Hex::new(self.q + vec.q, self.r + vec.r, self.s + vec.s)
+	}
+
+	fn neighbor(self, direction: usize) -> Self {
+		self.add(&HEX_DIRECTION_VECTORS[direction])
+	}
+
+	fn scale(self, factor: isize) -> Self {
+		Hex::new(self.q * factor, self.r * factor, self.s * factor)
+	}
+}
+
+/// Rings of hexagons spiraling out, leaving the center free (eg: for a button)
+#[derive(Debug, Clone)]
+pub struct HexLayout {
+	/// Size of a tile, padding included
+	pub size: f32,
+}
+impl Layout for HexLayout {
+	fn positions(&self, count: usize) -> Vec<Vector3<f32>> {
+		let mut positions = Vec::with_capacity(count);
+		let mut radius = 1;
+		while positions.len() < count {
+			let mut hex = HEX_CENTER.add(&HEX_DIRECTION_VECTORS[4].clone().scale(radius));
+			for i in 0..6 {
+				for _ in 0..radius {
+					if positions.len() == count {
+						return positions;
+					}
+					positions.push(hex.get_coords(self.size));
+					hex = hex.neighbor(i);
+				}
+			}
+			radius += 1;
+		}
+		positions
+	}
+}
+
+#[test]
+fn test_hex_layout() {
+	let positions = HexLayout { size: 0.1 }.positions(7);
+	assert_eq!(positions.len(), 7);
+	// The first ring has 6 tiles, the 7th starts the second ring further out
+	let distance = |p: &Vector3<f32>| (p.x.powi(2) + p.y.powi(2)).sqrt();
+	assert!(positions[..6]
+		.iter()
+		.all(|p| (distance(p) - distance(&positions[0])).abs() < 0.001));
+	assert!(distance(&positions[6]) > distance(&positions[0]));
+}
+
+/// Part of the space around the parent that tiles have to stay out of, eg: for a menu bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReservedRegion {
+	/// Every position less than `half_width` radians from the `center` direction, with directions
+	/// being angles in the XY plane (0 along +X, counterclockwise)
+	Arc { center: f32, half_width: f32 },
+	/// An axis aligned box
+	Box {
+		min: Vector3<f32>,
+		max: Vector3<f32>,
+	},
+}
+impl ReservedRegion {
+	pub fn contains(&self, position: Vector3<f32>) -> bool {
+		match *self {
+			ReservedRegion::Arc { center, half_width } => {
+				let angle = position.y.atan2(position.x);
+				let difference = (angle - center + PI).rem_euclid(2.0 * PI) - PI;
+				difference.abs() < half_width
+			}
+			ReservedRegion::Box { min, max } => {
+				(min.x..=max.x).contains(&position.x)
+					&& (min.y..=max.y).contains(&position.y)
+					&& (min.z..=max.z).contains(&position.z)
+			}
+		}
+	}
+}
+
+/// `layout`, with the positions that fall in `reserved` skipped and the tiles moved further along
+#[derive(Debug, Clone)]
+pub struct AvoidingLayout<L: Layout> {
+	pub layout: L,
+	pub reserved: ReservedRegion,
+}
+impl<L: Layout> Layout for AvoidingLayout<L> {
+	/// Can return fewer than `count` positions if `reserved` covers most of the layout
+	fn positions(&self, count: usize) -> Vec<Vector3<f32>> {
+		// Layouts give the same first positions no matter the count, so ask for more until enough are left
+		let max_requested = count * 16 + 64;
+		let mut requested = count;
+		loop {
+			let mut positions: Vec<_> = self
+				.layout
+				.positions(requested)
+				.into_iter()
+				.filter(|position| !self.reserved.contains(*position))
+				.collect();
+			if positions.len() >= count || requested >= max_requested {
+				positions.truncate(count);
+				return positions;
+			}
+			requested = (requested * 2).max(1).min(max_requested);
+		}
+	}
+}
+
+#[test]
+fn test_avoiding_layout() {
+	// The bottom of the rings stays clear
+	let reserved = ReservedRegion::Arc {
+		center: -PI / 2.0,
+		half_width: PI / 4.0,
+	};
+	let layout = AvoidingLayout {
+		layout: HexLayout { size: 0.1 },
+		reserved,
+	};
+	let positions = layout.positions(20);
+	assert_eq!(positions.len(), 20);
+	assert!(positions.iter().all(|p| !reserved.contains(*p)));
+	assert!(HexLayout { size: 0.1 }
+		.positions(20)
+		.iter()
+		.any(|p| reserved.contains(*p)));
+
+	let reserved = ReservedRegion::Box {
+		min: [-0.05, -0.05, -1.0].into(),
+		max: [0.15, 0.05, 1.0].into(),
+	};
+	let positions = AvoidingLayout {
+		layout: GridLayout {
+			columns: 3,
+			spacing: 0.1,
+		},
+		reserved,
+	}
+	.positions(4);
+	assert_eq!(
+		positions,
+		[
+			Vector3::from([0.2, 0.0, 0.0]),
+			Vector3::from([0.0, 0.1, 0.0]),
+			Vector3::from([0.1, 0.1, 0.0]),
+			Vector3::from([0.2, 0.1, 0.0]),
+		]
+	);
+
+	// Nowhere left to put tiles
+	let everywhere = ReservedRegion::Arc {
+		center: 0.0,
+		half_width: 4.0,
+	};
+	let layout = AvoidingLayout {
+		layout: HexLayout { size: 0.1 },
+		reserved: everywhere,
+	};
+	assert!(layout.positions(3).is_empty());
+}
+
+/// Where a tile goes and how it's turned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TilePose {
+	pub position: Vector3<f32>,
+	pub rotation: Quaternion<f32>,
+}
+
+/// Turns the tiles at `positions` so their front (+Z) points at `head_position`, keeping them upright.
+/// Only the position of the head matters, not where it's looking.
+pub fn facing_head(head_position: Vector3<f32>, positions: &[Vector3<f32>]) -> Vec<TilePose> {
+	let head = Vec3::from(head_position);
+	positions
+		.iter()
+		.map(|position| {
+			let forward = (head - Vec3::from(*position)).normalize_or_zero();
+			let right = Vec3::Y.cross(forward).normalize_or_zero();
+			let rotation = if forward == Vec3::ZERO {
+				Quat::IDENTITY
+			} else if right == Vec3::ZERO {
+				// Straight above or below the tile, there's no upright way to face it
+				Quat::from_rotation_arc(Vec3::Z, forward)
+			} else {
+				Quat::from_mat3(&Mat3::from_cols(right, forward.cross(right), forward))
+			};
+			TilePose {
+				position: *position,
+				rotation: rotation.into(),
+			}
+		})
+		.collect()
+}
+
+#[test]
+fn test_facing_head() {
+	let head = Vec3::new(0.0, 1.6, 0.0);
+	let positions = [
+		Vector3::from([0.0, 1.6, -1.0]),
+		Vector3::from([1.0, 1.0, -1.0]),
+		Vector3::from([-0.5, 2.0, 0.5]),
+		Vector3::from([0.0, 0.0, 0.0]),
+	];
+	let poses = facing_head(head.into(), &positions);
+	assert_eq!(poses.len(), positions.len());
+	for pose in &poses {
+		let rotation = Quat::from(pose.rotation);
+		let forward = rotation * Vec3::Z;
+		let to_head = (head - Vec3::from(pose.position)).normalize();
+		assert!(
+			forward.abs_diff_eq(to_head, 0.0001),
+			"{forward} != {to_head}"
+		);
+		// Upright, so the tile's right stays horizontal
+		assert!((rotation * Vec3::X).y.abs() < 0.0001);
+	}
+	// Straight in front of the head, its front already faces it
+	assert!(Quat::from(poses[0].rotation).abs_diff_eq(Quat::IDENTITY, 0.0001));
+}
+
+/// The area a page of tiles has to fit in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageRegion {
+	/// A flat rectangle, eg: part of a wall, in meters
+	Wall { width: f32, height: f32 },
+	/// Part of a dome `radius` meters around the user, with its arcs in radians
+	Dome {
+		radius: f32,
+		horizontal_arc: f32,
+		vertical_arc: f32,
+	},
+}
+impl PageRegion {
+	/// Width and height along the surface, in meters
+	fn extent(&self) -> (f32, f32) {
+		match *self {
+			PageRegion::Wall { width, height } => (width, height),
+			PageRegion::Dome {
+				radius,
+				horizontal_arc,
+				vertical_arc,
+			} => (radius * horizontal_arc, radius * vertical_arc),
+		}
+	}
+}
+
+/// How many tiles go on a page, as many as fit in `region` but no fewer than `min_tiles`
+/// and no more than `max_tiles`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageFit {
+	pub region: PageRegion,
+	/// Width of a tile, eg: `APP_SIZE`
+	pub tile_size: f32,
+	/// Gap between tiles
+	pub spacing: f32,
+	pub min_tiles: usize,
+	pub max_tiles: usize,
+}
+impl PageFit {
+	pub fn page_size(&self) -> usize {
+		let (width, height) = self.region.extent();
+		let pitch = self.tile_size + self.spacing;
+		// The last tile of a row or column doesn't need spacing after it
+		let fitting = |extent: f32| {
+			if pitch > 0.0 {
+				((extent + self.spacing) / pitch).floor().max(0.0) as usize
+			} else {
+				0
+			}
+		};
+		let max_tiles = self.max_tiles.max(self.min_tiles);
+		(fitting(width) * fitting(height)).clamp(self.min_tiles, max_tiles)
+	}
+	/// How many pages `tile_count` tiles take
+	pub fn page_count(&self, tile_count: usize) -> usize {
+		let page_size = self.page_size().max(1);
+		tile_count.div_ceil(page_size)
+	}
+}
+
+#[test]
+fn test_page_fit() {
+	let fit = PageFit {
+		region: PageRegion::Wall {
+			width: 0.5,
+			height: 0.3,
+		},
+		tile_size: 0.05,
+		spacing: 0.01,
+		min_tiles: 4,
+		max_tiles: 100,
+	};
+	// 8 columns of 6cm fit in 50cm (with no gap after the last), 5 rows in 30cm
+	assert_eq!(fit.page_size(), 40);
+	assert_eq!(fit.page_count(81), 3);
+	assert_eq!(fit.page_count(0), 0);
+
+	// Small region, big tiles, clamped to the minimum
+	let cramped = PageFit {
+		region: PageRegion::Wall {
+			width: 0.2,
+			height: 0.2,
+		},
+		tile_size: 0.15,
+		..fit
+	};
+	assert_eq!(cramped.page_size(), 4);
+
+	let huge = PageFit {
+		max_tiles: 24,
+		..fit
+	};
+	assert_eq!(huge.page_size(), 24);
+
+	// A quarter of a dome 1m away is about 1.57m wide
+	let dome = PageFit {
+		region: PageRegion::Dome {
+			radius: 1.0,
+			horizontal_arc: PI / 2.0,
+			vertical_arc: PI / 8.0,
+		},
+		max_tiles: 200,
+		..fit
+	};
+	assert_eq!(dome.page_size(), 26 * 6);
+	let dome_page = PageFit {
+		max_tiles: 100,
+		..dome
+	};
+	assert_eq!(dome_page.page_size(), 100);
+}
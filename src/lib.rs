@@ -1,3 +1,14 @@
 pub mod application;
+pub mod atlas;
+pub mod category;
+pub mod color;
+pub mod config;
+pub mod exec;
+pub mod layout;
+mod pool;
 pub mod protostar;
+pub mod search;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod watch;
 pub mod xdg;
@@ -0,0 +1,70 @@
+use lazy_static::lazy_static;
+use std::{
+	panic::{self, AssertUnwindSafe},
+	sync::{mpsc, Arc, Mutex},
+	thread,
+};
+
+/// How many icons get resolved at once, so hundreds of tiles don't start hundreds of threads
+pub(crate) const ICON_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed number of threads running jobs in the order they come in
+pub(crate) struct WorkerPool {
+	jobs: Mutex<mpsc::Sender<Job>>,
+}
+impl WorkerPool {
+	pub(crate) fn new(workers: usize) -> Self {
+		let (jobs, receiver) = mpsc::channel::<Job>();
+		let receiver = Arc::new(Mutex::new(receiver));
+		for _ in 0..workers {
+			let receiver = receiver.clone();
+			thread::spawn(move || loop {
+				// Only locked while waiting for a job, the others run meanwhile
+				let job = receiver.lock().unwrap().recv();
+				let Ok(job) = job else { break };
+				// A job that panics doesn't take the worker down with it
+				let _ = panic::catch_unwind(AssertUnwindSafe(job));
+			});
+		}
+		WorkerPool {
+			jobs: Mutex::new(jobs),
+		}
+	}
+	pub(crate) fn execute(&self, job: impl FnOnce() + Send + 'static) {
+		let _ = self.jobs.lock().unwrap().send(Box::new(job));
+	}
+}
+
+lazy_static! {
+	pub(crate) static ref ICON_POOL: WorkerPool = WorkerPool::new(ICON_WORKERS);
+}
+
+#[test]
+fn test_worker_pool_limit() {
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+	let pool = WorkerPool::new(2);
+	let running = Arc::new(AtomicUsize::new(0));
+	let most_running = Arc::new(AtomicUsize::new(0));
+	let (done_sender, done) = mpsc::channel();
+	for _ in 0..6 {
+		let running = running.clone();
+		let most_running = most_running.clone();
+		let done_sender = done_sender.clone();
+		pool.execute(move || {
+			let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+			most_running.fetch_max(now_running, Ordering::SeqCst);
+			thread::sleep(Duration::from_millis(20));
+			running.fetch_sub(1, Ordering::SeqCst);
+			done_sender.send(()).unwrap();
+		});
+	}
+	for _ in 0..6 {
+		done.recv_timeout(Duration::from_secs(5)).unwrap();
+	}
+	assert!((1..=2).contains(&most_running.load(Ordering::SeqCst)));
+}
@@ -1,41 +1,600 @@
 use crate::{
-	application::Application,
-	xdg::{DesktopFile, Icon, IconType},
+	application::{disambiguated_labels, Application},
+	category::{inferred_category, main_category, CategoryMatching, OTHER_CATEGORY},
+	color::Rgba,
+	layout::Layout,
+	pool::ICON_POOL,
+	xdg::{
+		icon_size_for_tile, DesktopFile, Icon, IconError, IconResolver, IconType,
+		DEFAULT_PIXELS_PER_DEGREE, DEFAULT_VIEWING_DISTANCE,
+	},
 };
 use color_eyre::eyre::Result;
 use glam::Quat;
-use mint::Vector3;
+use mint::{Quaternion, Vector3};
+use serde::{Deserialize, Serialize};
 use stardust_xr_fusion::{
 	client::{FrameInfo, RootHandler},
 	core::values::Transform,
 	drawable::{Alignment, Bounds, MaterialParameter, Model, ResourceID, Text, TextFit, TextStyle},
 	fields::BoxField,
-	node::NodeType,
+	input::{InputData, InputHandler},
+	node::{NodeError, NodeType},
 	spatial::Spatial,
+	HandlerWrapper,
 };
-use stardust_xr_molecules::{GrabData, Grabbable};
-use std::f32::consts::PI;
+use stardust_xr_molecules::{
+	input_action::{BaseInputAction, InputActionHandler},
+	GrabData, Grabbable,
+};
+use std::{
+	collections::HashMap,
+	f32::consts::PI,
+	fmt::{self, Debug},
+	future::Future,
+	sync::Arc,
+	time::Duration,
+};
+use tokio::sync::oneshot::{self, error::TryRecvError};
 use tween::{QuartInOut, Tweener};
 
 const MODEL_SCALE: f32 = 0.03;
 const ACTIVATION_DISTANCE: f32 = 0.5;
+const HOVER_DISTANCE: f32 = 0.05;
+const DEFAULT_HEX_COLOR: Rgba = Rgba::new(0.0, 1.0, 1.0, 1.0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LabelColor {
+	Fixed(Rgba),
+	/// Black or white, whichever is more readable on the hexagon color
+	Auto,
+}
+
+pub type Hook = Arc<dyn Fn(&Application) + Send + Sync>;
+
+/// Callbacks for a front-end to give feedback (eg: sounds or haptics) on tile interactions
+#[derive(Clone, Default)]
+pub struct ProtoStarHooks {
+	pub on_hover: Option<Hook>,
+	pub on_grab_start: Option<Hook>,
+	pub on_launch: Option<Hook>,
+}
+impl ProtoStarHooks {
+	fn dispatch(&self, application: &Application, events: TileEvents) {
+		let hooks = [
+			(events.hover_start, &self.on_hover),
+			(events.grab_start, &self.on_grab_start),
+		];
+		for (happened, hook) in hooks {
+			if let (true, Some(hook)) = (happened, hook) {
+				hook(application);
+			}
+		}
+	}
+}
+impl Debug for ProtoStarHooks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ProtoStarHooks")
+			.field("on_hover", &self.on_hover.is_some())
+			.field("on_grab_start", &self.on_grab_start.is_some())
+			.field("on_launch", &self.on_launch.is_some())
+			.finish()
+	}
+}
+
+/// What happened to a tile during a frame
+#[derive(Debug, Clone, Copy, Default)]
+struct TileEvents {
+	hover_start: bool,
+	grab_start: bool,
+}
+
+#[test]
+fn test_hooks_dispatch() {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	let grabs = Arc::new(AtomicUsize::new(0));
+	let hovers = Arc::new(AtomicUsize::new(0));
+	let hooks = ProtoStarHooks {
+		on_grab_start: Some({
+			let grabs = grabs.clone();
+			Arc::new(move |_: &Application| {
+				grabs.fetch_add(1, Ordering::Relaxed);
+			})
+		}),
+		on_hover: Some({
+			let hovers = hovers.clone();
+			Arc::new(move |_: &Application| {
+				hovers.fetch_add(1, Ordering::Relaxed);
+			})
+		}),
+		..Default::default()
+	};
+	let application = Application::create(DesktopFile::default()).unwrap();
+
+	hooks.dispatch(
+		&application,
+		TileEvents {
+			grab_start: true,
+			..Default::default()
+		},
+	);
+	assert_eq!(grabs.load(Ordering::Relaxed), 1);
+	assert_eq!(hovers.load(Ordering::Relaxed), 0);
+	// No hooks installed is a no-op
+	ProtoStarHooks::default().dispatch(
+		&application,
+		TileEvents {
+			hover_start: true,
+			grab_start: true,
+		},
+	);
+}
+
+#[derive(Debug, Clone)]
+pub struct ProtoStarConfig {
+	/// Color of the hexagon behind PNG icons, alpha makes it translucent
+	pub hex_color: Rgba,
+	pub label_color: LabelColor,
+	/// Rotation applied to glTF icons, eg: to make them face the user
+	pub gltf_orientation: Quat,
+	/// Slow spin of glTF icons around their vertical axis while idle, in radians per second
+	pub gltf_idle_spin: Option<f32>,
+	/// Desktop ID to a multiplier of the tile size, for icons that look too big or small
+	pub scale_overrides: HashMap<String, f32>,
+	pub hooks: ProtoStarHooks,
+	/// How the icons of tiles are resolved, eg: with the category icon for apps without one
+	pub icon_resolver: IconResolver,
+	/// What to show instead of a missing icon, tried in order
+	pub icon_fallbacks: Vec<IconFallbackStep>,
+	/// Cut labels longer than this many characters and end them with an ellipsis
+	pub label_max_chars: Option<usize>,
+	/// How long a tile has to stay grabbed past the activation distance for releasing it to launch,
+	/// so brief grabs don't launch by accident
+	pub launch_dwell: Duration,
+	/// Show names in this locale (eg: `de`) instead of the environment's one
+	pub locale: Option<String>,
+	/// Label tiles with the vendor full name (eg: `X-GNOME-FullName`) instead of `Name` when there's one
+	pub prefer_full_name: bool,
+	/// Tell apart tiles of apps with the same name in `create_protostars`, eg: `Settings (System Settings)`
+	pub disambiguate_names: bool,
+	pub grab_policy: GrabPolicy,
+}
+impl Default for ProtoStarConfig {
+	fn default() -> Self {
+		ProtoStarConfig {
+			hex_color: DEFAULT_HEX_COLOR,
+			label_color: LabelColor::Fixed(Rgba::WHITE),
+			gltf_orientation: Quat::IDENTITY,
+			gltf_idle_spin: None,
+			scale_overrides: HashMap::new(),
+			hooks: ProtoStarHooks::default(),
+			icon_resolver: IconResolver::default(),
+			icon_fallbacks: vec![IconFallbackStep::Letter, IconFallbackStep::Neutral],
+			label_max_chars: None,
+			launch_dwell: Duration::ZERO,
+			locale: None,
+			prefer_full_name: false,
+			disambiguate_names: false,
+			grab_policy: GrabPolicy::Launch,
+		}
+	}
+}
+impl ProtoStarConfig {
+	/// Scale of the hexagon model of the application with `desktop_id`, the rest of the tile follows it
+	pub fn model_scale(&self, desktop_id: &str) -> f32 {
+		MODEL_SCALE * self.scale_overrides.get(desktop_id).copied().unwrap_or(1.0)
+	}
+	/// Rotation and idle spin glTF icons start with
+	fn gltf_pose(&self) -> (Quat, Option<IdleSpin>) {
+		(
+			self.gltf_orientation,
+			self.gltf_idle_spin.map(IdleSpin::new),
+		)
+	}
+	/// `name` cut to `label_max_chars` characters, plus an ellipsis if anything was cut
+	pub fn label_text(&self, name: &str) -> String {
+		match self.label_max_chars {
+			Some(max_chars) if name.chars().count() > max_chars => {
+				let mut label: String = name.chars().take(max_chars).collect();
+				label.truncate(label.trim_end().len());
+				label.push('…');
+				label
+			}
+			_ => name.to_string(),
+		}
+	}
+}
+
+#[test]
+fn test_scale_overrides() {
+	let config = ProtoStarConfig {
+		scale_overrides: HashMap::from([("big.desktop".to_string(), 1.5)]),
+		..Default::default()
+	};
+	assert_eq!(config.model_scale("other.desktop"), MODEL_SCALE);
+	assert_eq!(config.model_scale("big.desktop"), MODEL_SCALE * 1.5);
+}
+
+#[test]
+fn test_label_max_chars() {
+	let config = ProtoStarConfig {
+		label_max_chars: Some(10),
+		..Default::default()
+	};
+	assert_eq!(config.label_text("LibreOffice Presentation"), "LibreOffic…");
+	assert_eq!(config.label_text("Firefox"), "Firefox");
+	assert_eq!(config.label_text("Visual Studio Code"), "Visual Stu…");
+	assert_eq!(
+		ProtoStarConfig::default().label_text("LibreOffice Presentation"),
+		"LibreOffice Presentation"
+	);
+}
+
+struct IdleSpin {
+	speed: f32,
+	angle: f32,
+}
+impl IdleSpin {
+	fn new(speed: f32) -> Self {
+		IdleSpin { speed, angle: 0.0 }
+	}
+	fn advance(&mut self, delta: f64) -> f32 {
+		self.angle = (self.angle + self.speed * delta as f32) % (PI * 2.0);
+		self.angle
+	}
+	/// `rotation` turned by the current angle around the vertical axis
+	fn spun(&self, rotation: Quat) -> Quat {
+		rotation * Quat::from_rotation_y(self.angle)
+	}
+}
+
+#[test]
+fn test_idle_spin_advances() {
+	let mut spin = IdleSpin::new(0.5);
+	let first = spin.advance(0.1);
+	let second = spin.advance(0.1);
+	assert!(first > 0.0);
+	assert!(second > first);
+}
+
+#[test]
+fn test_gltf_config() {
+	let config = ProtoStarConfig {
+		gltf_orientation: Quat::from_rotation_x(PI * 0.5),
+		gltf_idle_spin: Some(1.0),
+		..Default::default()
+	};
+	let (rotation, idle_spin) = config.gltf_pose();
+	assert_eq!(rotation, config.gltf_orientation);
+	let mut idle_spin = idle_spin.unwrap();
+	// Every frame turns the model further from the configured orientation
+	let mut previous = idle_spin.spun(rotation);
+	assert!(previous.abs_diff_eq(rotation, 1e-6));
+	for _ in 0..3 {
+		idle_spin.advance(0.25);
+		let current = idle_spin.spun(rotation);
+		assert!(current.angle_between(rotation) > previous.angle_between(rotation));
+		previous = current;
+	}
+
+	let (rotation, idle_spin) = ProtoStarConfig::default().gltf_pose();
+	assert_eq!(rotation, Quat::IDENTITY);
+	assert!(idle_spin.is_none());
+}
+
+/// What pulling a tile past the activation distance and letting go of it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrabPolicy {
+	#[default]
+	Launch,
+	/// Leaves a copy of the tile where it was let go (eg: on a shelf), pulling the copy launches
+	Duplicate,
+}
 
-fn model_from_icon(parent: &Spatial, icon: &Icon) -> Result<Model> {
+#[derive(Debug, PartialEq, Eq)]
+enum PullOutcome {
+	Launch,
+	Duplicate,
+}
+
+fn pull_outcome(policy: GrabPolicy, is_duplicate: bool) -> PullOutcome {
+	match policy {
+		GrabPolicy::Duplicate if !is_duplicate => PullOutcome::Duplicate,
+		_ => PullOutcome::Launch,
+	}
+}
+
+#[test]
+fn test_pull_outcome() {
+	assert_eq!(pull_outcome(GrabPolicy::Launch, false), PullOutcome::Launch);
+	// The first pull only makes a copy, pulling the copy launches
+	assert_eq!(
+		pull_outcome(GrabPolicy::Duplicate, false),
+		PullOutcome::Duplicate
+	);
+	assert_eq!(
+		pull_outcome(GrabPolicy::Duplicate, true),
+		PullOutcome::Launch
+	);
+}
+
+/// Whether the application of a tile is running or a favorite, shown as a badge in its corner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TileStatus {
+	pub running: bool,
+	pub favorite: bool,
+}
+impl TileStatus {
+	/// What the badge shows, there's only room for one so favorites win over running apps
+	fn badge_glyph(self) -> Option<&'static str> {
+		if self.favorite {
+			Some("★")
+		} else if self.running {
+			Some("●")
+		} else {
+			None
+		}
+	}
+}
+
+#[test]
+fn test_badge_glyph() {
+	let mut status = TileStatus::default();
+	assert_eq!(status.badge_glyph(), None);
+	status.favorite = true;
+	assert_eq!(status.badge_glyph(), Some("★"));
+	status.running = true;
+	assert_eq!(status.badge_glyph(), Some("★"));
+	status.favorite = false;
+	assert_eq!(status.badge_glyph(), Some("●"));
+	status.running = false;
+	assert_eq!(status.badge_glyph(), None);
+}
+
+/// The node showing the badge of a tile
+trait BadgeNode {
+	fn set_glyph(&self, glyph: &str) -> Result<(), NodeError>;
+	fn set_shown(&self, shown: bool) -> Result<(), NodeError>;
+}
+impl BadgeNode for Text {
+	fn set_glyph(&self, glyph: &str) -> Result<(), NodeError> {
+		self.set_text(glyph)
+	}
+	fn set_shown(&self, shown: bool) -> Result<(), NodeError> {
+		self.set_enabled(shown)
+	}
+}
+
+/// Shows the glyph of `status` on `badge` while the tile is shown, hides it when there's none
+fn show_badge(
+	badge: &impl BadgeNode,
+	status: TileStatus,
+	tile_shown: bool,
+) -> Result<(), NodeError> {
+	match status.badge_glyph() {
+		Some(glyph) => {
+			badge.set_glyph(glyph)?;
+			badge.set_shown(tile_shown)
+		}
+		None => badge.set_shown(false),
+	}
+}
+
+#[test]
+fn test_show_badge() {
+	#[derive(Default)]
+	struct FakeBadge {
+		glyph: std::cell::RefCell<String>,
+		shown: std::cell::Cell<bool>,
+	}
+	impl BadgeNode for FakeBadge {
+		fn set_glyph(&self, glyph: &str) -> Result<(), NodeError> {
+			*self.glyph.borrow_mut() = glyph.to_string();
+			Ok(())
+		}
+		fn set_shown(&self, shown: bool) -> Result<(), NodeError> {
+			self.shown.set(shown);
+			Ok(())
+		}
+	}
+	let badge = FakeBadge::default();
+	let running = TileStatus {
+		running: true,
+		favorite: false,
+	};
+	show_badge(&badge, running, true).unwrap();
+	assert!(badge.shown.get());
+	assert_eq!(*badge.glyph.borrow(), "●");
+
+	show_badge(&badge, TileStatus::default(), true).unwrap();
+	assert!(!badge.shown.get());
+
+	// Hidden tiles keep their badge hidden until they're shown
+	show_badge(&badge, running, false).unwrap();
+	assert!(!badge.shown.get());
+}
+
+/// Transform of a tile relative to its parent as plain arrays, so it can be saved (eg: to persist
+/// where tiles were left) and given back to the server later
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileTransform {
+	pub position: [f32; 3],
+	/// Quaternion, `[x, y, z, w]`
+	pub rotation: [f32; 4],
+	pub scale: [f32; 3],
+}
+impl From<(Vector3<f32>, Quaternion<f32>, Vector3<f32>)> for TileTransform {
+	fn from((position, rotation, scale): (Vector3<f32>, Quaternion<f32>, Vector3<f32>)) -> Self {
+		TileTransform {
+			position: position.into(),
+			rotation: rotation.into(),
+			scale: scale.into(),
+		}
+	}
+}
+impl From<TileTransform> for Transform {
+	fn from(transform: TileTransform) -> Self {
+		Transform::from_position_rotation_scale(
+			transform.position,
+			Quaternion::from(transform.rotation),
+			transform.scale,
+		)
+	}
+}
+
+#[test]
+fn test_tile_transform_round_trip() {
+	let transform = TileTransform::from((
+		Vector3::from([0.1, -0.2, 0.3]),
+		Quat::from_rotation_y(PI * 0.25).into(),
+		Vector3::from([1.0; 3]),
+	));
+	let json = serde_json::to_string(&transform).unwrap();
+	assert_eq!(
+		serde_json::from_str::<TileTransform>(&json).unwrap(),
+		transform
+	);
+	let rotation = Quat::from_array(transform.rotation);
+	assert!(rotation.abs_diff_eq(Quat::from_rotation_y(PI * 0.25), 1e-6));
+	assert_eq!(transform.position, [0.1, -0.2, 0.3]);
+}
+
+/// Distance of a tile from where it sits in the grid, compared to `ACTIVATION_DISTANCE`
+fn grab_distance(offset: Vector3<f32>) -> f32 {
+	((offset.x.powi(2) + offset.y.powi(2)).sqrt() + offset.z.powi(2)).sqrt()
+}
+
+/// Time a grabbed tile has continuously spent past the activation distance
+struct LaunchDwell {
+	required: f64,
+	held: f64,
+}
+impl LaunchDwell {
+	fn new(required: Duration) -> Self {
+		LaunchDwell {
+			required: required.as_secs_f64(),
+			held: 0.0,
+		}
+	}
+	fn advance(&mut self, past_activation_distance: bool, delta: f64) {
+		if past_activation_distance {
+			self.held += delta;
+		} else {
+			self.held = 0.0;
+		}
+	}
+	fn reset(&mut self) {
+		self.held = 0.0;
+	}
+	fn satisfied(&self) -> bool {
+		self.held >= self.required
+	}
+}
+
+#[test]
+fn test_launch_dwell() {
+	let mut dwell = LaunchDwell::new(Duration::from_millis(500));
+	// Pulled out briefly, then back in before the dwell passed
+	dwell.advance(true, 0.2);
+	assert!(!dwell.satisfied());
+	dwell.advance(false, 0.1);
+	dwell.advance(true, 0.4);
+	assert!(!dwell.satisfied());
+	dwell.advance(true, 0.1);
+	assert!(dwell.satisfied());
+	dwell.reset();
+	assert!(!dwell.satisfied());
+
+	assert!(LaunchDwell::new(Duration::ZERO).satisfied());
+}
+
+/// What letting go of a tile `position` away from where it sits does: nothing if it wasn't pulled out
+/// far or long enough, otherwise `launch`, or with a `duplicate_sender` a copy of the tile left there
+fn release_pull(
+	position: Vector3<f32>,
+	dwell_satisfied: bool,
+	duplicate_sender: Option<oneshot::Sender<Vector3<f32>>>,
+	launch: impl FnOnce(),
+) {
+	if grab_distance(position) <= ACTIVATION_DISTANCE || !dwell_satisfied {
+		return;
+	}
+	match duplicate_sender {
+		Some(duplicate_sender) => {
+			let _ = duplicate_sender.send(position);
+		}
+		None => launch(),
+	}
+}
+
+#[test]
+fn test_release_pull_duplicate() {
+	let pulled_out = Vector3::from([0.0, 0.0, 1.0]);
+	// The tile gets the position to leave its copy at, without launching
+	let (duplicate_sender, mut pending_duplicate) = oneshot::channel();
+	let mut launched = false;
+	release_pull(pulled_out, true, Some(duplicate_sender), || launched = true);
+	assert!(!launched);
+	assert_eq!(pending_duplicate.try_recv().unwrap(), pulled_out);
+
+	// Let go near the grid, no copy
+	let (duplicate_sender, mut pending_duplicate) = oneshot::channel();
+	release_pull(
+		Vector3::from([0.0, 0.0, 0.1]),
+		true,
+		Some(duplicate_sender),
+		|| launched = true,
+	);
+	assert!(!launched);
+	assert!(matches!(
+		pending_duplicate.try_recv(),
+		Err(TryRecvError::Closed)
+	));
+
+	// Pulling a copy launches
+	release_pull(pulled_out, true, None, || launched = true);
+	assert!(launched);
+}
+
+#[test]
+fn test_release_pull_before_dwell() {
+	let pulled_out = Vector3::from([0.0, 0.0, 1.0]);
+	let mut dwell = LaunchDwell::new(Duration::from_millis(500));
+	let mut launched = false;
+	// Let go far enough, but before the dwell time passed
+	dwell.advance(true, 0.2);
+	release_pull(pulled_out, dwell.satisfied(), None, || launched = true);
+	assert!(!launched);
+
+	dwell.advance(true, 0.3);
+	release_pull(pulled_out, dwell.satisfied(), None, || launched = true);
+	assert!(launched);
+}
+
+/// Lays the hexagon model flat, facing the user
+fn hexagon_rotation() -> Quat {
+	Quat::from_rotation_x(PI / 2.0) * Quat::from_rotation_y(PI)
+}
+
+fn model_from_icon(
+	parent: &Spatial,
+	icon: &Icon,
+	config: &ProtoStarConfig,
+	model_scale: f32,
+) -> Result<Model> {
 	return match &icon.icon_type {
 		IconType::Png => {
-			let t = Transform::from_rotation_scale(
-				Quat::from_rotation_x(PI / 2.0) * Quat::from_rotation_y(PI),
-				[MODEL_SCALE; 3],
-			);
+			let t = Transform::from_rotation_scale(hexagon_rotation(), [model_scale; 3]);
 
 			let model = Model::create(
 				parent,
 				t,
 				&ResourceID::new_namespaced("protostar", "hexagon/hexagon"),
 			)?;
-			model
-				.model_part("Hex")?
-				.set_material_parameter("color", MaterialParameter::Color([0.0, 1.0, 1.0, 1.0]))?;
+			model.model_part("Hex")?.set_material_parameter(
+				"color",
+				MaterialParameter::Color(config.hex_color.to_array()),
+			)?;
 			model.model_part("Icon")?.set_material_parameter(
 				"diffuse",
 				MaterialParameter::Texture(ResourceID::Direct(icon.path.clone())),
@@ -44,21 +603,342 @@ fn model_from_icon(parent: &Spatial, icon: &Icon) -> Result<Model> {
 		}
 		IconType::Gltf => Ok(Model::create(
 			parent,
-			Transform::from_scale([0.05; 3]),
+			Transform::from_rotation_scale(
+				config.gltf_orientation,
+				[0.05 * model_scale / MODEL_SCALE; 3],
+			),
 			&ResourceID::new_direct(icon.path.clone())?,
 		)?),
 		_ => panic!("Invalid Icon Type"),
 	};
 }
 
+/// A step of `ProtoStarConfig::icon_fallbacks`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFallbackStep {
+	/// The standard icon of the application's main category, if the icon theme has it
+	CategoryIcon,
+	/// The hexagon tinted with a color of the application's main category, with the category's
+	/// standard icon on it if the icon theme has it, for applications in a main category
+	TintedCategory,
+	/// The first letter of the name on the hexagon, only for applications whose icon is missing or
+	/// broken, not for the ones that don't declare any
+	Letter,
+	/// The plain hexagon, always works
+	Neutral,
+}
+
+/// What a tile shows when it has no icon
+#[derive(Debug, Clone, PartialEq)]
+enum IconFallback {
+	Icon(Icon),
+	Letter(char),
+	Tinted { color: Rgba, glyph: Option<Icon> },
+	Neutral,
+}
+/// Tries each of `steps` in turn, the plain hexagon if none of them work
+fn resolve_icon_fallback(
+	application: &Application,
+	error: &IconError,
+	resolver: &IconResolver,
+	steps: &[IconFallbackStep],
+	icon_size: u16,
+) -> IconFallback {
+	steps
+		.iter()
+		.find_map(|step| match step {
+			IconFallbackStep::CategoryIcon => resolver
+				.icon_for_category(
+					&main_category(application, CategoryMatching::Exact),
+					icon_size,
+				)
+				.map(IconFallback::Icon),
+			IconFallbackStep::TintedCategory => {
+				let category = match main_category(application, CategoryMatching::Exact) {
+					category if category != OTHER_CATEGORY => category,
+					_ => inferred_category(application)?.to_string(),
+				};
+				let glyph = resolver
+					.icon_for_category(&category, icon_size)
+					.filter(|icon| icon.icon_type == IconType::Png);
+				Some(IconFallback::Tinted {
+					color: Rgba::hashed(&category),
+					glyph,
+				})
+			}
+			IconFallbackStep::Letter => match error {
+				IconError::NotDeclared => None,
+				IconError::NotFound | IconError::RenderFailed(_) => application
+					.name()
+					.and_then(|name| name.chars().find(|c| c.is_alphanumeric()))
+					.map(|c| IconFallback::Letter(c.to_uppercase().next().unwrap_or(c))),
+			},
+			IconFallbackStep::Neutral => Some(IconFallback::Neutral),
+		})
+		.unwrap_or(IconFallback::Neutral)
+}
+
+/// Size the icon of a tile of `model_scale` gets rasterized at
+fn tile_icon_size(model_scale: f32) -> u16 {
+	icon_size_for_tile(
+		model_scale * 2.0,
+		DEFAULT_VIEWING_DISTANCE,
+		DEFAULT_PIXELS_PER_DEGREE,
+	)
+}
+
+/// How big the parts of a tile are, they all follow the scale of its hexagon model
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TileSize {
+	model_scale: f32,
+	/// Side of the box field the tile gets grabbed by
+	field_size: f32,
+	/// Pixels the icon gets rasterized at
+	icon_size: u16,
+}
+impl TileSize {
+	fn new(model_scale: f32) -> Self {
+		TileSize {
+			model_scale,
+			field_size: model_scale * 2.0,
+			icon_size: tile_icon_size(model_scale),
+		}
+	}
+}
+
+#[test]
+fn test_tile_size_overrides() {
+	let config = ProtoStarConfig {
+		scale_overrides: HashMap::from([("big.desktop".to_string(), 1.5)]),
+		..Default::default()
+	};
+	let default = TileSize::new(config.model_scale("other.desktop"));
+	let big = TileSize::new(config.model_scale("big.desktop"));
+	assert_eq!(default, TileSize::new(MODEL_SCALE));
+	assert_eq!(big.model_scale, default.model_scale * 1.5);
+	assert_eq!(big.field_size, default.field_size * 1.5);
+	assert!(big.icon_size > default.icon_size);
+}
+
+/// Resolves the icon of a tile on the icon workers, it (or what to show instead) comes through the receiver
+fn resolve_tile_icon(
+	application: &Application,
+	icon_size: u16,
+	resolver: &IconResolver,
+	fallbacks: &[IconFallbackStep],
+) -> oneshot::Receiver<Result<Icon, IconFallback>> {
+	let (icon_sender, pending_icon) = oneshot::channel();
+	let application = application.clone();
+	let resolver = resolver.clone();
+	let fallbacks = fallbacks.to_vec();
+	ICON_POOL.execute(move || {
+		let icon = application
+			.try_backend_ready_icon_with(&resolver, icon_size, false)
+			.map_err(|error| {
+				resolve_icon_fallback(&application, &error, &resolver, &fallbacks, icon_size)
+			});
+		let _ = icon_sender.send(icon);
+	});
+	pending_icon
+}
+
+#[test]
+fn test_resolve_tile_icon_theme_change() {
+	let xdg = crate::test_utils::FakeXdg::new();
+	for theme in ["light", "dark"] {
+		xdg.add_icon_theme(theme, &["hicolor"], &["48x48/apps"]);
+	}
+	xdg.add_icon_theme("hicolor", &[], &["48x48/apps"]);
+	let light_icon = xdg.add_icon("light", "48x48/apps", "themed-editor.png", &[]);
+	let dark_icon = xdg.add_icon("dark", "48x48/apps", "themed-editor.png", &[]);
+	let application = Application::create(DesktopFile {
+		name: Some("Editor".into()),
+		icon: Some("themed-editor".into()),
+		..Default::default()
+	})
+	.unwrap();
+	let resolve = |resolver: &IconResolver| {
+		resolve_tile_icon(&application, 48, resolver, &[IconFallbackStep::Neutral])
+			.blocking_recv()
+			.unwrap()
+			.map(|icon| icon.path)
+	};
+
+	let mut resolver = IconResolver::new().cache_dir(xdg.cache_home());
+	resolver.set_theme(Some("light".into()));
+	assert_eq!(resolve(&resolver), Ok(light_icon));
+	// What `ProtoStar::refresh_icon` resolves after the theme changed
+	resolver.set_theme(Some("dark".into()));
+	assert_eq!(resolve(&resolver), Ok(dark_icon));
+	// Icons remembered for another theme don't leak into the system one
+	resolver.set_theme(None);
+	assert_eq!(resolve(&resolver), Err(IconFallback::Neutral));
+}
+
+#[test]
+fn test_icon_fallback() {
+	let _env = crate::test_utils::lock_env();
+	let application = |icon: Option<&str>| {
+		Application::create(DesktopFile {
+			name: Some("inkscape".into()),
+			icon: icon.map(String::from),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let resolver = IconResolver::default();
+	let steps = ProtoStarConfig::default().icon_fallbacks;
+
+	let iconless = application(None);
+	let error = iconless.try_icon(64, false).unwrap_err();
+	assert_eq!(
+		resolve_icon_fallback(&iconless, &error, &resolver, &steps, 64),
+		IconFallback::Neutral
+	);
+
+	let missing = application(Some("/nonexistent/protostar-missing-icon.png"));
+	let error = missing.try_icon(64, false).unwrap_err();
+	assert_eq!(
+		resolve_icon_fallback(&missing, &error, &resolver, &steps, 64),
+		IconFallback::Letter('I')
+	);
+
+	let broken = IconError::RenderFailed(std::io::ErrorKind::InvalidData.into());
+	assert_eq!(
+		resolve_icon_fallback(&missing, &broken, &resolver, &steps, 64),
+		IconFallback::Letter('I')
+	);
+}
+
+#[test]
+fn test_icon_fallback_category_icon() {
+	let xdg = crate::test_utils::FakeXdg::new();
+	xdg.add_icon_theme("hicolor", &[], &["48x48/apps"]);
+	let category_icon = xdg.add_icon("hicolor", "48x48/apps", "applications-development.png", &[]);
+	let resolver = IconResolver::new().cache_dir(xdg.cache_home());
+	let application = Application::create(DesktopFile {
+		name: Some("IDE".into()),
+		categories: vec!["Development".into()],
+		..Default::default()
+	})
+	.unwrap();
+
+	let steps = [
+		IconFallbackStep::CategoryIcon,
+		IconFallbackStep::Letter,
+		IconFallbackStep::Neutral,
+	];
+	let fallback =
+		resolve_icon_fallback(&application, &IconError::NotDeclared, &resolver, &steps, 48);
+	let IconFallback::Icon(icon) = fallback else {
+		panic!("expected the category icon, got {:?}", fallback)
+	};
+	assert_eq!(icon.path, category_icon);
+}
+
+#[test]
+fn test_icon_fallback_tinted_category() {
+	let xdg = crate::test_utils::FakeXdg::new();
+	xdg.add_icon_theme("hicolor", &[], &["48x48/apps"]);
+	let category_icon = xdg.add_icon("hicolor", "48x48/apps", "applications-development.png", &[]);
+	let resolver = IconResolver::new().cache_dir(xdg.cache_home());
+	let application = |categories: &[&str], keywords: &[&str]| {
+		Application::create(DesktopFile {
+			name: Some("IDE".into()),
+			categories: categories.iter().map(|c| c.to_string()).collect(),
+			keywords: keywords.iter().map(|k| k.to_string()).collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let steps = [IconFallbackStep::TintedCategory, IconFallbackStep::Neutral];
+
+	let fallback = resolve_icon_fallback(
+		&application(&["Development"], &[]),
+		&IconError::NotDeclared,
+		&resolver,
+		&steps,
+		48,
+	);
+	let IconFallback::Tinted { color, glyph } = fallback else {
+		panic!("expected a tinted hexagon, got {:?}", fallback)
+	};
+	assert_eq!(color, Rgba::hashed("Development"));
+	assert_ne!(color, DEFAULT_HEX_COLOR);
+	assert_eq!(glyph.unwrap().path, category_icon);
+
+	// Categorized by keywords, tinted the same
+	let inferred = resolve_icon_fallback(
+		&application(&[], &["Programming"]),
+		&IconError::NotDeclared,
+		&resolver,
+		&steps,
+		48,
+	);
+	assert!(matches!(inferred, IconFallback::Tinted { color: c, .. } if c == color));
+	// Tinted even without an icon for the category
+	let games = resolve_icon_fallback(
+		&application(&["Game"], &[]),
+		&IconError::NotDeclared,
+		&resolver,
+		&steps,
+		48,
+	);
+	assert_eq!(
+		games,
+		IconFallback::Tinted {
+			color: Rgba::hashed("Game"),
+			glyph: None
+		}
+	);
+	assert_eq!(
+		resolve_icon_fallback(
+			&application(&[], &[]),
+			&IconError::NotDeclared,
+			&resolver,
+			&steps,
+			48
+		),
+		IconFallback::Neutral
+	);
+}
+
+/// What the icon model of a tile currently is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconModel {
+	/// The hexagon as it's created
+	Hexagon,
+	/// The hexagon with an icon, a tint or a glyph on it
+	DecoratedHexagon,
+	Gltf,
+}
+
 pub struct ProtoStar {
 	application: Application,
+	config: ProtoStarConfig,
+	model_scale: f32,
 	parent: Spatial,
+	launch_space: Option<Spatial>,
 	position: Vector3<f32>,
 	grabbable: Grabbable,
 	_field: BoxField,
+	input: HandlerWrapper<InputHandler, InputActionHandler<()>>,
+	hover_action: BaseInputAction<()>,
 	icon: Model,
+	pending_icon: Option<oneshot::Receiver<Result<Icon, IconFallback>>>,
+	icon_model: IconModel,
+	fallback_glyph: Option<Text>,
+	icon_rotation: Quat,
+	idle_spin: Option<IdleSpin>,
+	launch_dwell: LaunchDwell,
+	past_activation_distance: bool,
+	pending_distance: Option<oneshot::Receiver<f32>>,
+	is_duplicate: bool,
+	pending_duplicate: Option<oneshot::Receiver<Vector3<f32>>>,
+	duplicates: Vec<ProtoStar>,
 	label: Option<Text>,
+	status: TileStatus,
+	badge: Option<Text>,
 	grabbable_shrink: Option<Tweener<f32, f64, QuartInOut>>,
 	grabbable_grow: Option<Tweener<f32, f64, QuartInOut>>,
 	grabbable_move: Option<Tweener<f32, f64, QuartInOut>>,
@@ -69,11 +949,32 @@ impl ProtoStar {
 		parent: &Spatial,
 		position: impl Into<Vector3<f32>>,
 		desktop_file: DesktopFile,
+	) -> Result<Self> {
+		Self::create_from_desktop_file_with_config(
+			parent,
+			position,
+			desktop_file,
+			ProtoStarConfig::default(),
+		)
+	}
+	pub fn create_from_desktop_file_with_config(
+		parent: &Spatial,
+		position: impl Into<Vector3<f32>>,
+		desktop_file: DesktopFile,
+		config: ProtoStarConfig,
+	) -> Result<Self> {
+		Self::create_from_application(parent, position, Application::create(desktop_file)?, config)
+	}
+	pub fn create_from_application(
+		parent: &Spatial,
+		position: impl Into<Vector3<f32>>,
+		application: Application,
+		config: ProtoStarConfig,
 	) -> Result<Self> {
 		let position = position.into();
-		let field = BoxField::create(parent, Transform::default(), [MODEL_SCALE * 2.0; 3])?;
-		let application = Application::create(&parent.client()?, desktop_file)?;
-		let icon = application.icon(128, false);
+		let size = TileSize::new(config.model_scale(&application.id()));
+		let model_scale = size.model_scale;
+		let field = BoxField::create(parent, Transform::default(), [size.field_size; 3])?;
 		let grabbable = Grabbable::create(
 			parent,
 			Transform::from_position(position),
@@ -85,21 +986,33 @@ impl ProtoStar {
 		)?;
 		grabbable.content_parent().set_spatial_parent(parent)?;
 		field.set_spatial_parent(grabbable.content_parent())?;
-		let icon = icon
-			.map(|i| model_from_icon(grabbable.content_parent(), &i))
-			.unwrap_or_else(|| {
-				Ok(Model::create(
-					grabbable.content_parent(),
-					Transform::from_rotation_scale(
-						Quat::from_rotation_x(PI / 2.0) * Quat::from_rotation_y(PI),
-						[MODEL_SCALE; 3],
-					),
-					&ResourceID::new_namespaced("protostar", "hexagon/hexagon"),
-				)?)
-			})?;
+		let input = InputHandler::create(grabbable.content_parent(), Transform::default(), &field)?
+			.wrap(InputActionHandler::new(()))?;
+		let hover_action = BaseInputAction::new(false, |input: &InputData, _: &()| {
+			input.distance < HOVER_DISTANCE
+		});
+
+		// Show the default hexagon right away and swap the real icon in once it's resolved
+		let icon_rotation = hexagon_rotation();
+		let icon = Model::create(
+			grabbable.content_parent(),
+			Transform::from_rotation_scale(icon_rotation, [model_scale; 3]),
+			&ResourceID::new_namespaced("protostar", "hexagon/hexagon"),
+		)?;
+		let pending_icon = resolve_tile_icon(
+			&application,
+			size.icon_size,
+			&config.icon_resolver,
+			&config.icon_fallbacks,
+		);
 
+		let label_color = match config.label_color {
+			LabelColor::Fixed(color) => color,
+			LabelColor::Auto => config.hex_color.contrasting_text_color(),
+		};
 		let label_style = TextStyle {
 			character_height: MODEL_SCALE * 4.0,
+			color: label_color.to_array(),
 			bounds: Some(Bounds {
 				bounds: [1.0; 2].into(),
 				fit: TextFit::Wrap,
@@ -108,26 +1021,63 @@ impl ProtoStar {
 			text_align: Alignment::Center.into(),
 			..Default::default()
 		};
-		let label = application.name().and_then(|name| {
-			Text::create(
-				&icon,
-				Transform::from_position_rotation(
-					[0.0, 0.1, -(MODEL_SCALE * 8.0)],
-					Quat::from_rotation_x(PI * 0.5),
-				),
-				name,
-				label_style,
-			)
-			.ok()
-		});
+		let label = application
+			.display_name(config.locale.as_deref(), config.prefer_full_name)
+			.and_then(|name| {
+				Text::create(
+					&icon,
+					Transform::from_position_rotation(
+						[0.0, 0.1, -(MODEL_SCALE * 8.0)],
+						Quat::from_rotation_x(PI * 0.5),
+					),
+					config.label_text(name),
+					label_style,
+				)
+				.ok()
+			});
+		let badge_style = TextStyle {
+			character_height: 0.4,
+			color: label_color.to_array(),
+			text_align: Alignment::Center.into(),
+			..Default::default()
+		};
+		let badge = Text::create(
+			&icon,
+			Transform::from_position_rotation([0.7, 0.1, -0.7], Quat::from_rotation_x(PI * 0.5)),
+			"",
+			badge_style,
+		)
+		.ok();
+		if let Some(badge) = &badge {
+			badge.set_enabled(false)?;
+		}
+		let launch_dwell = LaunchDwell::new(config.launch_dwell);
 		Ok(ProtoStar {
+			config,
+			model_scale,
 			parent: parent.alias(),
+			launch_space: None,
 			position,
 			grabbable,
 			_field: field,
+			input,
+			hover_action,
 			label,
+			status: TileStatus::default(),
+			badge,
 			application,
 			icon,
+			pending_icon: Some(pending_icon),
+			icon_model: IconModel::Hexagon,
+			fallback_glyph: None,
+			icon_rotation,
+			idle_spin: None,
+			launch_dwell,
+			past_activation_distance: false,
+			pending_distance: None,
+			is_duplicate: false,
+			pending_duplicate: None,
+			duplicates: Vec::new(),
 			grabbable_shrink: None,
 			grabbable_grow: None,
 			grabbable_move: None,
@@ -137,6 +1087,220 @@ impl ProtoStar {
 	pub fn content_parent(&self) -> &Spatial {
 		self.grabbable.content_parent()
 	}
+	/// Moves the tile, it also goes back here after being pulled
+	pub fn set_position(&mut self, position: impl Into<Vector3<f32>>) -> Result<()> {
+		self.position = position.into();
+		self.content_parent()
+			.set_position(Some(&self.parent), self.position)?;
+		Ok(())
+	}
+	/// The current transform of the tile relative to its parent, eg: to persist where it was left.
+	/// Reading it takes a round trip to the server, so this gives a future to await it with.
+	pub fn transform(&self) -> Result<impl Future<Output = Result<TileTransform>>> {
+		let transform = self
+			.content_parent()
+			.get_position_rotation_scale(&self.parent)?;
+		Ok(async move { Ok(TileTransform::from(transform.await?)) })
+	}
+	/// Scales the tile around its position, eg: to fade it out and back in
+	pub fn set_scale(&self, scale: f32) -> Result<()> {
+		self.content_parent()
+			.set_scale(Some(&self.parent), Vector3::from([scale; 3]))?;
+		Ok(())
+	}
+	/// Windows of the application get parented to `launch_space` instead of to the tile, `None` resets it
+	pub fn set_launch_space(&mut self, launch_space: Option<&Spatial>) {
+		self.launch_space = launch_space.map(|space| space.alias());
+	}
+	fn set_fallback(&mut self, fallback: IconFallback) -> Result<()> {
+		if self.icon_model != IconModel::Hexagon && !matches!(fallback, IconFallback::Icon(_)) {
+			// Nothing of the previous icon should show through, eg: after `refresh_icon`
+			self.reset_icon_model()?;
+		}
+		let letter = match fallback {
+			IconFallback::Icon(icon) => return self.set_icon(icon),
+			IconFallback::Letter(letter) => letter,
+			IconFallback::Tinted { color, glyph } => {
+				self.icon_model = IconModel::DecoratedHexagon;
+				self.icon
+					.model_part("Hex")?
+					.set_material_parameter("color", MaterialParameter::Color(color.to_array()))?;
+				if let Some(glyph) = glyph {
+					self.icon.model_part("Icon")?.set_material_parameter(
+						"diffuse",
+						MaterialParameter::Texture(ResourceID::Direct(glyph.path)),
+					)?;
+				}
+				return Ok(());
+			}
+			IconFallback::Neutral => return Ok(()),
+		};
+		let glyph_style = TextStyle {
+			character_height: 1.0,
+			color: self.config.hex_color.contrasting_text_color().to_array(),
+			text_align: Alignment::Center.into(),
+			..Default::default()
+		};
+		self.fallback_glyph = Some(Text::create(
+			&self.icon,
+			Transform::from_position_rotation([0.0, 0.1, 0.0], Quat::from_rotation_x(PI * 0.5)),
+			&letter.to_string(),
+			glyph_style,
+		)?);
+		self.icon_model = IconModel::DecoratedHexagon;
+		Ok(())
+	}
+	fn set_icon(&mut self, icon: Icon) -> Result<()> {
+		match icon.icon_type {
+			IconType::Png => {
+				if self.icon_model == IconModel::Gltf {
+					self.reset_icon_model()?;
+				}
+				self.fallback_glyph = None;
+				self.icon_model = IconModel::DecoratedHexagon;
+				self.icon.model_part("Hex")?.set_material_parameter(
+					"color",
+					MaterialParameter::Color(self.config.hex_color.to_array()),
+				)?;
+				self.icon.model_part("Icon")?.set_material_parameter(
+					"diffuse",
+					MaterialParameter::Texture(ResourceID::Direct(icon.path)),
+				)?;
+			}
+			IconType::Gltf => {
+				let model =
+					model_from_icon(self.content_parent(), &icon, &self.config, self.model_scale)?;
+				self.replace_icon_model(model)?;
+				self.icon_model = IconModel::Gltf;
+				(self.icon_rotation, self.idle_spin) = self.config.gltf_pose();
+			}
+			_ => (),
+		}
+		Ok(())
+	}
+	/// Swaps the icon model for `model`, keeping the label and badge
+	fn replace_icon_model(&mut self, model: Model) -> Result<()> {
+		model.set_enabled(self.currently_shown)?;
+		if let Some(label) = &self.label {
+			label.set_spatial_parent_in_place(&model)?;
+		}
+		if let Some(badge) = &self.badge {
+			badge.set_spatial_parent_in_place(&model)?;
+		}
+		self.fallback_glyph = None;
+		self.icon = model;
+		Ok(())
+	}
+	/// Puts the plain hexagon the tile is created with back in place of the icon model
+	fn reset_icon_model(&mut self) -> Result<()> {
+		let hexagon = Model::create(
+			self.content_parent(),
+			Transform::from_rotation_scale(hexagon_rotation(), [self.model_scale; 3]),
+			&ResourceID::new_namespaced("protostar", "hexagon/hexagon"),
+		)?;
+		self.replace_icon_model(hexagon)?;
+		self.icon_model = IconModel::Hexagon;
+		self.icon_rotation = hexagon_rotation();
+		self.idle_spin = None;
+		Ok(())
+	}
+	/// Resolves the icon again with `resolver`, eg: after `IconResolver::set_theme`, and swaps it in
+	/// once it's ready like when the tile is created. A PNG icon only replaces the texture of the
+	/// hexagon, a glTF one (or going back from one) replaces the model, the grabbable stays either way.
+	pub fn refresh_icon(&mut self, resolver: &IconResolver) {
+		self.config.icon_resolver = resolver.clone();
+		self.pending_icon = Some(resolve_tile_icon(
+			&self.application,
+			tile_icon_size(self.model_scale),
+			&self.config.icon_resolver,
+			&self.config.icon_fallbacks,
+		));
+	}
+	/// Copies of this tile left behind by pulls under `GrabPolicy::Duplicate`
+	pub fn duplicates(&self) -> &[ProtoStar] {
+		&self.duplicates
+	}
+	fn duplicate_at(&mut self, position: Vector3<f32>) -> Result<()> {
+		let mut duplicate = ProtoStar::create_from_application(
+			&self.parent,
+			position,
+			self.application.clone(),
+			self.config.clone(),
+		)?;
+		duplicate.is_duplicate = true;
+		duplicate.launch_space = self.launch_space.as_ref().map(|space| space.alias());
+		self.duplicates.push(duplicate);
+		Ok(())
+	}
+	pub fn status(&self) -> TileStatus {
+		self.status
+	}
+	/// Shows the badge matching `status`, or hides it
+	pub fn set_status(&mut self, status: TileStatus) -> Result<()> {
+		self.status = status;
+		self.update_badge()
+	}
+	pub fn set_running(&mut self, running: bool) -> Result<()> {
+		self.set_status(TileStatus {
+			running,
+			..self.status
+		})
+	}
+	pub fn set_favorite(&mut self, favorite: bool) -> Result<()> {
+		self.set_status(TileStatus {
+			favorite,
+			..self.status
+		})
+	}
+	/// Replaces the text of the label, cut to `label_max_chars` like the name it starts with
+	pub fn set_label(&self, name: &str) -> Result<()> {
+		if let Some(label) = &self.label {
+			label.set_text(self.config.label_text(name))?;
+		}
+		Ok(())
+	}
+	fn update_badge(&self) -> Result<()> {
+		let Some(badge) = &self.badge else { return Ok(()) };
+		show_badge(badge, self.status, self.currently_shown)?;
+		Ok(())
+	}
+	/// Keeps track of how long the tile has been grabbed past the activation distance
+	fn update_launch_dwell(&mut self, delta: f64) {
+		if self.grabbable.grab_action().actor_started() {
+			self.launch_dwell.reset();
+			self.past_activation_distance = false;
+		}
+		if !self.grabbable.grab_action().actor_acting() {
+			return;
+		}
+		if let Some(pending_distance) = &mut self.pending_distance {
+			match pending_distance.try_recv() {
+				Ok(distance) => {
+					self.pending_distance = None;
+					self.past_activation_distance = distance > ACTIVATION_DISTANCE;
+				}
+				Err(TryRecvError::Empty) => (),
+				Err(TryRecvError::Closed) => self.pending_distance = None,
+			}
+		}
+		// One position request in flight at a time, the dwell uses the latest answer meanwhile
+		if self.pending_distance.is_none() {
+			if let Ok(position_future) = self
+				.content_parent()
+				.get_position_rotation_scale(&self.parent)
+			{
+				let (distance_sender, pending_distance) = oneshot::channel();
+				tokio::task::spawn(async move {
+					if let Ok((position, _, _)) = position_future.await {
+						let _ = distance_sender.send(grab_distance(position));
+					}
+				});
+				self.pending_distance = Some(pending_distance);
+			}
+		}
+		self.launch_dwell
+			.advance(self.past_activation_distance, delta);
+	}
 	pub fn toggle(&mut self) {
 		self.grabbable.set_enabled(!self.currently_shown).unwrap();
 		if self.currently_shown {
@@ -144,14 +1308,665 @@ impl ProtoStar {
 		} else {
 			self.icon.set_enabled(true).unwrap();
 			self.label.as_ref().map(|l| l.set_enabled(true).unwrap());
+			if let Some(badge) = &self.badge {
+				show_badge(badge, self.status, true).unwrap();
+			}
 			self.grabbable_move = Some(Tweener::quart_in_out(0.0001, 1.0, 0.25));
 		}
 		self.currently_shown = !self.currently_shown;
 	}
 }
+
+/// Creates a tile for each of the (already filtered and sorted) `applications`, placed by `layout`.
+/// Applications that fail to get a tile are skipped.
+pub fn create_protostars(
+	parent: &Spatial,
+	applications: Vec<Application>,
+	layout: &impl Layout,
+	config: &ProtoStarConfig,
+) -> Vec<ProtoStar> {
+	create_tiles(applications, layout, config, |application, position| {
+		ProtoStar::create_from_application(parent, position, application, config.clone()).ok()
+	})
+	.into_iter()
+	.map(|(protostar, label)| {
+		if let Some(label) = label {
+			let _ = protostar.set_label(&label);
+		}
+		protostar
+	})
+	.collect()
+}
+
+/// What `create_protostars` does without a server, `create` makes a tile or fails for an application.
+/// Each tile comes with its label when names get disambiguated.
+fn create_tiles<T>(
+	applications: Vec<Application>,
+	layout: &impl Layout,
+	config: &ProtoStarConfig,
+	mut create: impl FnMut(Application, Vector3<f32>) -> Option<T>,
+) -> Vec<(T, Option<String>)> {
+	let positions = layout.positions(applications.len());
+	let labels = if config.disambiguate_names {
+		disambiguated_labels(
+			&applications,
+			config.locale.as_deref(),
+			config.prefer_full_name,
+		)
+	} else {
+		vec![None; applications.len()]
+	};
+	applications
+		.into_iter()
+		.zip(positions)
+		.zip(labels)
+		.filter_map(|((application, position), label)| {
+			Some((create(application, position)?, label))
+		})
+		.collect()
+}
+
+#[test]
+fn test_create_protostars() {
+	use crate::layout::GridLayout;
+	use std::path::PathBuf;
+	let app = |id: &str, name: &str| {
+		Application::create(DesktopFile {
+			path: PathBuf::from(format!("/usr/share/applications/{}.desktop", id)),
+			name: Some(name.into()),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let applications = vec![
+		app("org.gnome.Settings", "Settings"),
+		app("broken", "Broken"),
+		app("org.kde.systemsettings", "Settings"),
+	];
+	let layout = GridLayout {
+		columns: 2,
+		spacing: 1.0,
+	};
+	let create = |application: Application, position: Vector3<f32>| {
+		(application.id() != "broken.desktop").then(|| (application.id(), position))
+	};
+
+	// Failing applications are skipped, the others keep the position the layout gave them
+	let tiles = create_tiles(
+		applications.clone(),
+		&layout,
+		&ProtoStarConfig::default(),
+		create,
+	);
+	assert_eq!(
+		tiles,
+		[
+			(
+				(
+					"org.gnome.Settings.desktop".to_string(),
+					Vector3::from([0.0, 0.0, 0.0])
+				),
+				None
+			),
+			(
+				(
+					"org.kde.systemsettings.desktop".to_string(),
+					Vector3::from([0.0, 1.0, 0.0])
+				),
+				None
+			),
+		]
+	);
+
+	let config = ProtoStarConfig {
+		disambiguate_names: true,
+		..Default::default()
+	};
+	let labels: Vec<_> = create_tiles(applications, &layout, &config, create)
+		.into_iter()
+		.map(|(_, label)| label.unwrap())
+		.collect();
+	assert_eq!(
+		labels,
+		["Settings (org.gnome.Settings)", "Settings (systemsettings)"]
+	);
+}
+
+/// Tiles keyed by desktop ID in layout order, with the position the layout gave each of them
+pub struct TileSet<T> {
+	tiles: Vec<(String, T, Vector3<f32>)>,
+}
+impl<T> Default for TileSet<T> {
+	fn default() -> Self {
+		TileSet { tiles: Vec::new() }
+	}
+}
+impl<T> TileSet<T> {
+	/// Adds `tile` at the end, or replaces the tile with the same ID in place and returns it
+	pub fn insert(&mut self, id: String, tile: T, position: Vector3<f32>) -> Option<T> {
+		match self.tiles.iter_mut().find(|(tile_id, _, _)| *tile_id == id) {
+			Some(existing) => Some(std::mem::replace(&mut existing.1, tile)),
+			None => {
+				self.tiles.push((id, tile, position));
+				None
+			}
+		}
+	}
+	pub fn remove(&mut self, id: &str) -> Option<T> {
+		let index = self
+			.tiles
+			.iter()
+			.position(|(tile_id, _, _)| tile_id == id)?;
+		Some(self.tiles.remove(index).1)
+	}
+	pub fn get(&self, id: &str) -> Option<&T> {
+		self.iter()
+			.find(|(tile_id, _)| *tile_id == id)
+			.map(|(_, tile)| tile)
+	}
+	pub fn contains(&self, id: &str) -> bool {
+		self.get(id).is_some()
+	}
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+	pub fn get_mut(&mut self, id: &str) -> Option<&mut T> {
+		self.tiles
+			.iter_mut()
+			.find(|(tile_id, _, _)| tile_id == id)
+			.map(|(_, tile, _)| tile)
+	}
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+		self.tiles.iter().map(|(id, tile, _)| (id.as_str(), tile))
+	}
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+		self.tiles.iter_mut().map(|(_, tile, _)| tile)
+	}
+	/// Where the next inserted tile goes with `layout`
+	pub fn next_position(&self, layout: &impl Layout) -> Vector3<f32> {
+		layout
+			.positions(self.len() + 1)
+			.pop()
+			.unwrap_or([0.0; 3].into())
+	}
+	/// Runs `layout` again for the current tiles, `move_tile` is only called for the ones whose position changed
+	pub fn relayout(
+		&mut self,
+		layout: &impl Layout,
+		mut move_tile: impl FnMut(&mut T, Vector3<f32>),
+	) {
+		let positions = layout.positions(self.len());
+		for ((_, tile, position), new_position) in self.tiles.iter_mut().zip(positions) {
+			if *position != new_position {
+				*position = new_position;
+				move_tile(tile, new_position);
+			}
+		}
+	}
+}
+
+#[test]
+fn test_tile_set() {
+	use crate::layout::GridLayout;
+	let layout = GridLayout {
+		columns: 2,
+		spacing: 1.0,
+	};
+	let mut tiles = TileSet::default();
+	let mut moved = Vec::new();
+	let add = |tiles: &mut TileSet<String>, id: &str| {
+		let position = tiles.next_position(&layout);
+		tiles.insert(id.to_string(), format!("{} tile", id), position);
+	};
+	for id in ["a", "b", "c", "d"] {
+		add(&mut tiles, id);
+	}
+	tiles.relayout(&layout, |tile, _| moved.push(tile.clone()));
+	assert!(
+		moved.is_empty(),
+		"tiles were placed where the layout puts them"
+	);
+	let positions: Vec<_> = tiles
+		.tiles
+		.iter()
+		.map(|(_, _, position)| *position)
+		.collect();
+
+	add(&mut tiles, "e");
+	tiles.relayout(&layout, |tile, _| moved.push(tile.clone()));
+	assert!(moved.is_empty());
+	assert_eq!(tiles.remove("e").as_deref(), Some("e tile"));
+
+	// Only the tiles after the removed one move up
+	assert_eq!(tiles.remove("b").as_deref(), Some("b tile"));
+	tiles.relayout(&layout, |tile, position| {
+		moved.push(tile.clone());
+		tile.push_str(" moved");
+		assert!(positions.contains(&position));
+	});
+	assert_eq!(moved, ["c tile", "d tile"]);
+	let remaining: Vec<_> = tiles.iter().map(|(id, tile)| (id, tile.as_str())).collect();
+	assert_eq!(
+		remaining,
+		[
+			("a", "a tile"),
+			("c", "c tile moved"),
+			("d", "d tile moved")
+		]
+	);
+	assert_eq!(tiles.tiles[1].2, positions[1]);
+	assert_eq!(tiles.remove("b"), None);
+
+	// Replacing keeps the place in the layout
+	assert_eq!(
+		tiles
+			.insert("a".to_string(), "new a".to_string(), [9.0; 3].into())
+			.as_deref(),
+		Some("a tile")
+	);
+	assert_eq!(tiles.get("a").map(String::as_str), Some("new a"));
+	assert_eq!(tiles.tiles[0].2, positions[0]);
+}
+
+/// How tiles get from their positions on one page to the ones on the next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageAnimation {
+	/// Straight to the new positions
+	Snap,
+	/// Tiles slide from their old positions to the new ones over `duration` seconds
+	Slide { duration: f64 },
+	/// Tiles shrink away at their old positions and grow back at the new ones, `duration` seconds in total
+	Fade { duration: f64 },
+}
+impl Default for PageAnimation {
+	fn default() -> Self {
+		PageAnimation::Slide { duration: 0.25 }
+	}
+}
+
+/// The tweens of a page change, one per moving tile, all started together so the page moves as one
+pub struct PageTransition {
+	animation: PageAnimation,
+	tiles: Vec<(
+		String,
+		Vector3<f32>,
+		Vector3<f32>,
+		Tweener<f32, f64, QuartInOut>,
+	)>,
+}
+impl PageTransition {
+	/// `moves` are the desktop ID, old and new position of the tiles.
+	/// `None` if there's nothing to animate, ie: with `PageAnimation::Snap` or if no tile moves.
+	pub fn new(
+		animation: PageAnimation,
+		moves: impl IntoIterator<Item = (String, Vector3<f32>, Vector3<f32>)>,
+	) -> Option<Self> {
+		let duration = match animation {
+			PageAnimation::Snap => return None,
+			PageAnimation::Slide { duration } | PageAnimation::Fade { duration } => duration,
+		};
+		if duration <= 0.0 {
+			return None;
+		}
+		let tiles: Vec<_> = moves
+			.into_iter()
+			.filter(|(_, from, to)| from != to)
+			.map(|(id, from, to)| (id, from, to, Tweener::quart_in_out(0.0, 1.0, duration)))
+			.collect();
+		(!tiles.is_empty()).then_some(PageTransition { animation, tiles })
+	}
+	/// Moves the tweens `delta` seconds forward, giving the desktop ID and position of each tile
+	/// now, and its scale if the animation changes it
+	pub fn advance(&mut self, delta: f64) -> Vec<(&str, Vector3<f32>, Option<f32>)> {
+		let animation = self.animation;
+		self.tiles
+			.iter_mut()
+			.map(|(id, from, to, tweener)| {
+				let progress = tweener.move_by(delta);
+				let (position, scale) = match animation {
+					PageAnimation::Fade { .. } if progress < 0.5 => {
+						(*from, Some(1.0 - progress * 2.0))
+					}
+					PageAnimation::Fade { .. } => (*to, Some(progress * 2.0 - 1.0)),
+					_ => (
+						glam::Vec3::from(*from).lerp((*to).into(), progress).into(),
+						None,
+					),
+				};
+				(id.as_str(), position, scale.map(|scale| scale.max(0.0001)))
+			})
+			.collect()
+	}
+	pub fn is_finished(&self) -> bool {
+		self.tiles.iter().all(|(.., tweener)| tweener.is_finished())
+	}
+}
+
+#[test]
+fn test_page_transition() {
+	let moves = || {
+		[
+			(
+				"a".to_string(),
+				[0.0, 0.0, 0.0].into(),
+				[2.0, 0.0, 0.0].into(),
+			),
+			(
+				"b".to_string(),
+				[1.0, 1.0, 0.0].into(),
+				[1.0, -1.0, 0.0].into(),
+			),
+			("c".to_string(), [5.0; 3].into(), [5.0; 3].into()),
+		]
+	};
+	assert!(PageTransition::new(PageAnimation::Snap, moves()).is_none());
+	assert!(PageTransition::new(PageAnimation::Slide { duration: 0.0 }, moves()).is_none());
+
+	let mut slide = PageTransition::new(PageAnimation::Slide { duration: 1.0 }, moves()).unwrap();
+	let positions: Vec<_> = slide
+		.advance(0.5)
+		.into_iter()
+		.map(|(id, position, scale)| (id.to_string(), [position.x, position.y, position.z], scale))
+		.collect();
+	// Halfway through the ease is halfway between the pages, tiles that don't move aren't tweened
+	assert_eq!(
+		positions,
+		[
+			("a".to_string(), [1.0, 0.0, 0.0], None),
+			("b".to_string(), [1.0, 0.0, 0.0], None)
+		]
+	);
+	assert!(!slide.is_finished());
+	let end: Vec<_> = slide
+		.advance(0.6)
+		.into_iter()
+		.map(|(_, position, _)| [position.x, position.y, position.z])
+		.collect();
+	assert_eq!(end, [[2.0, 0.0, 0.0], [1.0, -1.0, 0.0]]);
+	assert!(slide.is_finished());
+
+	let mut fade = PageTransition::new(PageAnimation::Fade { duration: 1.0 }, moves()).unwrap();
+	let (_, position, scale) = fade.advance(0.25)[0];
+	assert_eq!([position.x, position.y, position.z], [0.0, 0.0, 0.0]);
+	assert!(scale.unwrap() > 0.0 && scale.unwrap() < 1.0);
+	let (_, position, scale) = fade.advance(0.75)[0];
+	assert_eq!([position.x, position.y, position.z], [2.0, 0.0, 0.0]);
+	assert_eq!(scale, Some(1.0));
+	assert!(fade.is_finished());
+}
+
+/// What page transitions need of a tile
+trait PageTile {
+	fn page_position(&self) -> Vector3<f32>;
+	fn move_to(&mut self, position: Vector3<f32>);
+	fn scale_to(&mut self, scale: f32);
+}
+impl PageTile for ProtoStar {
+	fn page_position(&self) -> Vector3<f32> {
+		self.position
+	}
+	fn move_to(&mut self, position: Vector3<f32>) {
+		let _ = self.set_position(position);
+	}
+	fn scale_to(&mut self, scale: f32) {
+		let _ = self.set_scale(scale);
+	}
+}
+
+/// Lays `tiles` out with `layout` again and gives the transition of `animation` taking them there from
+/// where they are now. Tiles start it at full scale, in case a fade got interrupted, and go straight
+/// to their positions when there's nothing to animate.
+fn start_page_transition<T: PageTile>(
+	tiles: &mut TileSet<T>,
+	layout: &impl Layout,
+	animation: PageAnimation,
+) -> Option<PageTransition> {
+	tiles.relayout(layout, |_, _| ());
+	for (_, tile, _) in &mut tiles.tiles {
+		tile.scale_to(1.0);
+	}
+	let moves = tiles
+		.tiles
+		.iter()
+		.map(|(id, tile, position)| (id.clone(), tile.page_position(), *position));
+	let transition = PageTransition::new(animation, moves);
+	if transition.is_none() {
+		for (_, tile, position) in &mut tiles.tiles {
+			if tile.page_position() != *position {
+				tile.move_to(*position);
+			}
+		}
+	}
+	transition
+}
+
+/// Moves the tiles of `transition` `delta` seconds further, and drops it once it's finished
+fn advance_page_transition<T: PageTile>(
+	tiles: &mut TileSet<T>,
+	transition: &mut Option<PageTransition>,
+	delta: f64,
+) {
+	let Some(running) = transition else { return };
+	for (id, position, scale) in running.advance(delta) {
+		let Some(tile) = tiles.get_mut(id) else {
+			continue;
+		};
+		tile.move_to(position);
+		if let Some(scale) = scale {
+			tile.scale_to(scale);
+		}
+	}
+	if running.is_finished() {
+		*transition = None;
+	}
+}
+
+#[test]
+fn test_remove_during_page_transition() {
+	use crate::layout::GridLayout;
+	struct FakeTile {
+		position: Vector3<f32>,
+		scale: f32,
+	}
+	impl PageTile for FakeTile {
+		fn page_position(&self) -> Vector3<f32> {
+			self.position
+		}
+		fn move_to(&mut self, position: Vector3<f32>) {
+			self.position = position;
+		}
+		fn scale_to(&mut self, scale: f32) {
+			self.scale = scale;
+		}
+	}
+	let grid = |columns| GridLayout {
+		columns,
+		spacing: 1.0,
+	};
+	let ids = ["a", "b", "c", "d"];
+	let new_tiles = || {
+		let mut tiles = TileSet::default();
+		for id in ids {
+			let position = tiles.next_position(&grid(2));
+			let tile = FakeTile {
+				position,
+				scale: 1.0,
+			};
+			tiles.insert(id.to_string(), tile, position);
+		}
+		tiles
+	};
+	// Every tile where the layout puts it, at full scale
+	let assert_settled = |tiles: &TileSet<FakeTile>, animation: PageAnimation| {
+		let ids: Vec<_> = tiles.iter().map(|(id, _)| id).collect();
+		assert_eq!(ids, ["a", "c", "d"]);
+		for ((_, tile), position) in tiles.iter().zip(grid(4).positions(3)) {
+			assert!(
+				glam::Vec3::from(tile.position).abs_diff_eq(position.into(), 1e-5),
+				"{:?}: {:?} instead of {:?}",
+				animation,
+				tile.position,
+				position
+			);
+			assert_eq!(tile.scale, 1.0);
+		}
+	};
+
+	for animation in [
+		PageAnimation::Slide { duration: 1.0 },
+		PageAnimation::Fade { duration: 1.0 },
+	] {
+		let mut tiles = new_tiles();
+		let mut transition = start_page_transition(&mut tiles, &grid(4), animation);
+		assert!(transition.is_some());
+		advance_page_transition(&mut tiles, &mut transition, 0.6);
+
+		// The tiles after the removed one go to their new places from where they are
+		tiles.remove("b");
+		transition = start_page_transition(&mut tiles, &grid(4), animation);
+		advance_page_transition(&mut tiles, &mut transition, 2.0);
+		assert!(transition.is_none());
+		assert_settled(&tiles, animation);
+
+		// Without an animation they snap there, back to full scale
+		let mut tiles = new_tiles();
+		let mut transition = start_page_transition(&mut tiles, &grid(4), animation);
+		advance_page_transition(&mut tiles, &mut transition, 0.6);
+		tiles.remove("b");
+		transition = start_page_transition(&mut tiles, &grid(4), PageAnimation::Snap);
+		assert!(transition.is_none());
+		assert_settled(&tiles, animation);
+	}
+}
+
+/// Tiles of applications keyed by desktop ID, so apps can be added and removed (eg: on live reload)
+/// without rebuilding every tile
+pub struct Launcher<L: Layout> {
+	parent: Spatial,
+	layout: L,
+	config: ProtoStarConfig,
+	tiles: TileSet<ProtoStar>,
+	page_animation: PageAnimation,
+	transition: Option<PageTransition>,
+}
+impl<L: Layout> Launcher<L> {
+	pub fn new(parent: &Spatial, layout: L, config: ProtoStarConfig) -> Self {
+		Launcher {
+			parent: parent.alias(),
+			layout,
+			config,
+			tiles: TileSet::default(),
+			page_animation: PageAnimation::default(),
+			transition: None,
+		}
+	}
+	pub fn set_page_animation(&mut self, page_animation: PageAnimation) {
+		self.page_animation = page_animation;
+	}
+	/// Switches to `layout`, eg: the one of another page, and animates the tiles to their new positions
+	/// with the page animation. Tiles in the middle of a transition start from where they are now.
+	pub fn set_layout(&mut self, layout: L) {
+		self.layout = layout;
+		self.transition = start_page_transition(&mut self.tiles, &self.layout, self.page_animation);
+	}
+	pub fn tiles(&self) -> &TileSet<ProtoStar> {
+		&self.tiles
+	}
+	/// Creates a tile for `application` after the others, or replaces the tile with the same ID in place
+	pub fn add_app(&mut self, application: Application) -> Result<()> {
+		let id = application.id();
+		let position = match self
+			.tiles
+			.tiles
+			.iter()
+			.find(|(tile_id, _, _)| *tile_id == id)
+		{
+			Some((_, _, position)) => *position,
+			None => self.tiles.next_position(&self.layout),
+		};
+		let protostar = ProtoStar::create_from_application(
+			&self.parent,
+			position,
+			application,
+			self.config.clone(),
+		)?;
+		self.tiles.insert(id, protostar, position);
+		Ok(())
+	}
+	/// Destroys the tile of the application with desktop ID `id` and moves the ones after it up,
+	/// from wherever they are if a page transition was running
+	pub fn remove_app(&mut self, id: &str) -> bool {
+		if self.tiles.remove(id).is_none() {
+			return false;
+		}
+		self.transition = start_page_transition(&mut self.tiles, &self.layout, self.page_animation);
+		true
+	}
+}
+impl<L: Layout> RootHandler for Launcher<L> {
+	fn frame(&mut self, info: FrameInfo) {
+		advance_page_transition(&mut self.tiles, &mut self.transition, info.delta);
+		for tile in self.tiles.iter_mut() {
+			tile.frame(info);
+		}
+	}
+}
+
 impl RootHandler for ProtoStar {
 	fn frame(&mut self, info: FrameInfo) {
 		let _ = self.grabbable.update(&info);
+		self.input
+			.lock_wrapped()
+			.update_actions([&mut self.hover_action]);
+		self.config.hooks.dispatch(
+			&self.application,
+			TileEvents {
+				hover_start: !self.hover_action.started_acting.is_empty(),
+				grab_start: self.grabbable.grab_action().actor_started(),
+			},
+		);
+
+		if let Some(pending_icon) = &mut self.pending_icon {
+			match pending_icon.try_recv() {
+				Ok(icon) => {
+					self.pending_icon = None;
+					let _ = match icon {
+						Ok(icon) => self.set_icon(icon),
+						Err(fallback) => self.set_fallback(fallback),
+					};
+				}
+				Err(TryRecvError::Empty) => (),
+				Err(TryRecvError::Closed) => self.pending_icon = None,
+			}
+		}
+
+		if !self.config.launch_dwell.is_zero() {
+			self.update_launch_dwell(info.delta);
+		}
+
+		if let Some(pending_duplicate) = &mut self.pending_duplicate {
+			match pending_duplicate.try_recv() {
+				Ok(position) => {
+					self.pending_duplicate = None;
+					let _ = self.duplicate_at(position);
+				}
+				Err(TryRecvError::Empty) => (),
+				Err(TryRecvError::Closed) => self.pending_duplicate = None,
+			}
+		}
+		for duplicate in &mut self.duplicates {
+			duplicate.frame(info);
+		}
+
+		if let Some(idle_spin) = &mut self.idle_spin {
+			idle_spin.advance(info.delta);
+			let _ = self
+				.icon
+				.set_rotation(None, idle_spin.spun(self.icon_rotation));
+		}
 
 		if let Some(grabbable_move) = &mut self.grabbable_move {
 			if !grabbable_move.is_finished() {
@@ -171,6 +1986,7 @@ impl RootHandler for ProtoStar {
 				if grabbable_move.final_value() == 0.0001 {
 					self.icon.set_enabled(false).unwrap();
 					self.label.as_ref().map(|l| l.set_enabled(false).unwrap());
+					self.badge.as_ref().map(|b| b.set_enabled(false).unwrap());
 				}
 				self.grabbable_move = None;
 			}
@@ -201,12 +2017,7 @@ impl RootHandler for ProtoStar {
 					.content_parent()
 					.set_rotation(Some(&self.parent), Quat::default())
 					.unwrap();
-				self.icon
-					.set_rotation(
-						None,
-						Quat::from_rotation_x(PI / 2.0) * Quat::from_rotation_y(PI),
-					)
-					.unwrap();
+				self.icon.set_rotation(None, self.icon_rotation).unwrap();
 			}
 		} else if let Some(grabbable_grow) = &mut self.grabbable_grow {
 			if !grabbable_grow.is_finished() {
@@ -230,17 +2041,32 @@ impl RootHandler for ProtoStar {
 				 else {return};
 
 			let application = self.application.clone();
-			let space = self.content_parent().alias();
+			let space = self
+				.launch_space
+				.as_ref()
+				.unwrap_or(self.content_parent())
+				.alias();
+			let on_launch = self.config.hooks.on_launch.clone();
+			let dwell_satisfied = self.launch_dwell.satisfied();
+			let duplicate_sender = match pull_outcome(self.config.grab_policy, self.is_duplicate) {
+				PullOutcome::Launch => None,
+				PullOutcome::Duplicate => {
+					let (duplicate_sender, pending_duplicate) = oneshot::channel();
+					self.pending_duplicate = Some(pending_duplicate);
+					Some(duplicate_sender)
+				}
+			};
 
 			//TODO: split the executable string for the args
 			tokio::task::spawn(async move {
-				let distance_vector = distance_future.await.ok().unwrap().0;
-				let distance = ((distance_vector.x.powi(2) + distance_vector.y.powi(2)).sqrt()
-					+ distance_vector.z.powi(2))
-				.sqrt();
-				if dbg!(distance) > ACTIVATION_DISTANCE {
-					let _ = application.launch(&space);
-				}
+				let position = distance_future.await.ok().unwrap().0;
+				release_pull(position, dwell_satisfied, duplicate_sender, || {
+					let _ = application.launch_with_callback(&space, |application| {
+						if let Some(on_launch) = on_launch {
+							on_launch(application);
+						}
+					});
+				});
 			});
 		}
 	}
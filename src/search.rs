@@ -0,0 +1,150 @@
+use crate::application::Application;
+
+/// How well `query` matches an application, higher is better, `None` if it doesn't match at all.
+/// Matching is case insensitive.
+pub fn score(application: &Application, query: &str) -> Option<u32> {
+	score_fields(
+		&query.to_lowercase(),
+		&application.name().unwrap_or_default().to_lowercase(),
+		&application
+			.generic_name()
+			.unwrap_or_default()
+			.to_lowercase(),
+		&application
+			.keywords()
+			.iter()
+			.map(|keyword| keyword.to_lowercase())
+			.collect::<Vec<_>>(),
+	)
+}
+
+/// `score` on fields that are already lowercase
+fn score_fields(query: &str, name: &str, generic_name: &str, keywords: &[String]) -> Option<u32> {
+	if query.is_empty() {
+		return Some(0);
+	}
+	let starts_word = |text: &str| text.split_whitespace().any(|word| word.starts_with(query));
+	if name == query {
+		Some(100)
+	} else if name.starts_with(query) {
+		Some(80)
+	} else if starts_word(name) {
+		Some(60)
+	} else if name.contains(query) {
+		Some(40)
+	} else if starts_word(generic_name) {
+		Some(30)
+	} else if keywords.iter().any(|keyword| keyword.starts_with(query)) {
+		Some(20)
+	} else if generic_name.contains(query) {
+		Some(10)
+	} else {
+		None
+	}
+}
+
+/// Applications with their searchable fields lowercased once up front, for search as you type
+pub struct SearchIndex {
+	entries: Vec<SearchEntry>,
+}
+struct SearchEntry {
+	application: Application,
+	name: String,
+	generic_name: String,
+	keywords: Vec<String>,
+}
+impl SearchIndex {
+	pub fn new(applications: impl IntoIterator<Item = Application>) -> Self {
+		let entries = applications
+			.into_iter()
+			.map(|application| SearchEntry {
+				name: application.name().unwrap_or_default().to_lowercase(),
+				generic_name: application
+					.generic_name()
+					.unwrap_or_default()
+					.to_lowercase(),
+				keywords: application
+					.keywords()
+					.iter()
+					.map(|keyword| keyword.to_lowercase())
+					.collect(),
+				application,
+			})
+			.collect();
+		SearchIndex { entries }
+	}
+
+	/// Matching applications with their score, best first, ties keep the order of the index
+	pub fn query(&self, query: &str) -> Vec<(Application, u32)> {
+		let query = query.to_lowercase();
+		let mut matches: Vec<_> = self
+			.entries
+			.iter()
+			.filter_map(|entry| {
+				score_fields(&query, &entry.name, &entry.generic_name, &entry.keywords)
+					.map(|score| (entry.application.clone(), score))
+			})
+			.collect();
+		matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+		matches
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+#[test]
+fn test_search_index() {
+	use crate::xdg::DesktopFile;
+	let app = |name: &str, generic_name: Option<&str>, keywords: &[&str]| {
+		Application::create(DesktopFile {
+			name: Some(name.to_string()),
+			generic_name: generic_name.map(str::to_string),
+			keywords: keywords.iter().map(|k| k.to_string()).collect(),
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let index = SearchIndex::new([
+		app(
+			"GNU Image Manipulation Program",
+			Some("Image Editor"),
+			&["GIMP"],
+		),
+		app("Firefox", Some("Web Browser"), &["Internet", "WWW"]),
+		app("Files", Some("File Manager"), &["folder"]),
+		app("Fire Drill", None, &[]),
+	]);
+	assert_eq!(index.len(), 4);
+	// Lowercased up front, not on every query
+	assert_eq!(index.entries[1].name, "firefox");
+	assert_eq!(index.entries[1].keywords, ["internet", "www"]);
+
+	let names = |query: &str| -> Vec<(String, u32)> {
+		index
+			.query(query)
+			.into_iter()
+			.map(|(app, score)| (app.name().unwrap().to_string(), score))
+			.collect()
+	};
+	assert_eq!(
+		names("FIRE"),
+		[("Firefox".to_string(), 80), ("Fire Drill".to_string(), 80)]
+	);
+	assert_eq!(names("firefox"), [("Firefox".to_string(), 100)]);
+	assert_eq!(names("browser"), [("Firefox".to_string(), 30)]);
+	assert_eq!(
+		names("gimp"),
+		[("GNU Image Manipulation Program".to_string(), 20)]
+	);
+	assert_eq!(names("image")[0].1, 60);
+	assert!(names("nothing matches this").is_empty());
+	assert_eq!(names("").len(), 4);
+
+	let firefox = &index.entries[1].application;
+	assert_eq!(score(firefox, "Web"), Some(30));
+}
@@ -0,0 +1,236 @@
+//! A throwaway XDG environment for tests, so discovery, icons and config don't depend on the machine
+//! running them. Enabled by the `test-utils` feature.
+
+use crate::xdg::get_image_cache_dir;
+use lazy_static::lazy_static;
+use std::{
+	env,
+	ffi::OsString,
+	fs,
+	path::{Path, PathBuf},
+	sync::{Mutex, MutexGuard},
+};
+use tempdir::TempDir;
+
+/// Every variable `FakeXdg` overrides, restored when it's dropped
+const XDG_VARS: [&str; 6] = [
+	"HOME",
+	"XDG_DATA_HOME",
+	"XDG_DATA_DIRS",
+	"XDG_CONFIG_HOME",
+	"XDG_CACHE_HOME",
+	"PATH",
+];
+
+lazy_static! {
+	// The environment is global to the process, so only one fake tree can be active at once
+	static ref XDG_ENV_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Locks the environment for a test that reads it (eg: `$PATH`, the XDG dirs, the icon cache) or
+/// spawns a program, so no `FakeXdg` swaps it in the meantime. Don't call it while holding a `FakeXdg`.
+pub fn lock_env() -> MutexGuard<'static, ()> {
+	// A test that panicked while holding the lock already restored the environment on drop
+	XDG_ENV_LOCK
+		.lock()
+		.unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A temporary home with its own data, config and cache dirs, plus one system data dir.
+/// The XDG variables point into it until it's dropped, then they're restored and it's deleted.
+///
+/// The icon cache dir is picked once per process, before the first `FakeXdg` changes anything, so
+/// it doesn't end up in a tree that gets deleted.
+pub struct FakeXdg {
+	root: TempDir,
+	saved_env: Vec<(&'static str, Option<OsString>)>,
+	_lock: MutexGuard<'static, ()>,
+}
+impl FakeXdg {
+	pub fn new() -> Self {
+		let lock = lock_env();
+		get_image_cache_dir();
+		let root = TempDir::new("protostar-xdg").expect("Could not create the fake XDG tree");
+		let saved_env = XDG_VARS
+			.iter()
+			.map(|var| (*var, env::var_os(var)))
+			.collect();
+		let fake_xdg = FakeXdg {
+			root,
+			saved_env,
+			_lock: lock,
+		};
+
+		for dir in [
+			fake_xdg.data_home(),
+			fake_xdg.system_data_dir(),
+			fake_xdg.config_home(),
+			fake_xdg.cache_home(),
+			fake_xdg.bin_dir(),
+		] {
+			fs::create_dir_all(dir).expect("Could not create the fake XDG tree");
+		}
+		env::set_var("HOME", fake_xdg.home());
+		env::set_var("XDG_DATA_HOME", fake_xdg.data_home());
+		env::set_var("XDG_DATA_DIRS", fake_xdg.system_data_dir());
+		env::set_var("XDG_CONFIG_HOME", fake_xdg.config_home());
+		env::set_var("XDG_CACHE_HOME", fake_xdg.cache_home());
+		env::set_var("PATH", fake_xdg.bin_dir());
+		fake_xdg
+	}
+
+	pub fn home(&self) -> PathBuf {
+		self.root.path().join("home")
+	}
+	pub fn data_home(&self) -> PathBuf {
+		self.home().join(".local").join("share")
+	}
+	/// The only dir in `$XDG_DATA_DIRS`, stands in for `/usr/share`
+	pub fn system_data_dir(&self) -> PathBuf {
+		self.root.path().join("usr").join("share")
+	}
+	pub fn config_home(&self) -> PathBuf {
+		self.home().join(".config")
+	}
+	pub fn cache_home(&self) -> PathBuf {
+		self.home().join(".cache")
+	}
+	/// The only dir in `$PATH`
+	pub fn bin_dir(&self) -> PathBuf {
+		self.root.path().join("bin")
+	}
+
+	/// Writes a desktop file to the user's applications dir
+	pub fn add_desktop_file(&self, id: &str, contents: &str) -> PathBuf {
+		write_file(&self.data_home().join("applications").join(id), contents)
+	}
+	/// Writes a desktop file to the system applications dir, which the user's dir overrides
+	pub fn add_system_desktop_file(&self, id: &str, contents: &str) -> PathBuf {
+		write_file(
+			&self.system_data_dir().join("applications").join(id),
+			contents,
+		)
+	}
+
+	/// Writes an `index.theme` for `theme` in the system icons dir, `directories` are eg: `48x48/apps`
+	pub fn add_icon_theme(&self, theme: &str, inherits: &[&str], directories: &[&str]) -> PathBuf {
+		let mut index = format!(
+			"[Icon Theme]\nName={}\nDirectories={}\n",
+			theme,
+			directories.join(",")
+		);
+		if !inherits.is_empty() {
+			index.push_str(&format!("Inherits={}\n", inherits.join(",")));
+		}
+		for directory in directories {
+			let size = directory
+				.split('x')
+				.next()
+				.and_then(|size| size.parse::<u16>().ok())
+				.unwrap_or(48);
+			let kind = if directory.starts_with("scalable") {
+				"Scalable"
+			} else {
+				"Fixed"
+			};
+			index.push_str(&format!(
+				"\n[{}]\nSize={}\nType={}\n",
+				directory, size, kind
+			));
+		}
+		write_file(
+			&self
+				.system_data_dir()
+				.join("icons")
+				.join(theme)
+				.join("index.theme"),
+			&index,
+		)
+	}
+	/// Writes an icon (eg: `firefox.png`) to `directory` (eg: `48x48/apps`) of `theme`
+	pub fn add_icon(
+		&self,
+		theme: &str,
+		directory: &str,
+		file_name: &str,
+		contents: &[u8],
+	) -> PathBuf {
+		let path = self
+			.system_data_dir()
+			.join("icons")
+			.join(theme)
+			.join(directory)
+			.join(file_name);
+		write_file(&path, contents)
+	}
+
+	/// Writes a protostar config file, eg: `blocklist`
+	pub fn add_config_file(&self, name: &str, contents: &str) -> PathBuf {
+		write_file(&self.config_home().join("protostar").join(name), contents)
+	}
+
+	/// Puts an executable shell script named `name` in `$PATH`
+	pub fn add_executable(&self, name: &str) -> PathBuf {
+		use std::os::unix::fs::PermissionsExt;
+		let path = write_file(&self.bin_dir().join(name), "#!/bin/sh\n");
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+			.expect("Could not make the fake executable executable");
+		path
+	}
+}
+impl Default for FakeXdg {
+	fn default() -> Self {
+		FakeXdg::new()
+	}
+}
+impl Drop for FakeXdg {
+	fn drop(&mut self) {
+		for (var, value) in &self.saved_env {
+			match value {
+				Some(value) => env::set_var(var, value),
+				None => env::remove_var(var),
+			}
+		}
+	}
+}
+
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> PathBuf {
+	fs::create_dir_all(path.parent().unwrap()).expect("Could not create the fake XDG tree");
+	fs::write(path, contents).expect("Could not write to the fake XDG tree");
+	path.to_path_buf()
+}
+
+#[test]
+fn test_fake_xdg_discovery() {
+	use crate::{
+		config::load_blocklist,
+		xdg::{discover_applications, DiscoveryConfig},
+	};
+
+	let original_home = {
+		let _env = lock_env();
+		env::var_os("HOME")
+	};
+	{
+		let xdg = FakeXdg::new();
+		xdg.add_system_desktop_file(
+			"editor.desktop",
+			"[Desktop Entry]\nType=Application\nName=System Editor\nExec=editor",
+		);
+		xdg.add_desktop_file(
+			"editor.desktop",
+			"[Desktop Entry]\nType=Application\nName=User Editor\nExec=editor",
+		);
+		xdg.add_system_desktop_file(
+			"blocked.desktop",
+			"[Desktop Entry]\nType=Application\nName=Blocked\nExec=blocked",
+		);
+		xdg.add_config_file("blocklist", "blocked.desktop\n");
+
+		let apps = discover_applications(&DiscoveryConfig::new().blocklist(load_blocklist()));
+		assert_eq!(apps.len(), 1);
+		assert_eq!(apps[0].name.as_deref(), Some("User Editor"));
+	}
+	let _env = lock_env();
+	assert_eq!(env::var_os("HOME"), original_home);
+}
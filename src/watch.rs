@@ -0,0 +1,99 @@
+use crate::xdg::{parse_desktop_file, DesktopFile};
+use std::{
+	fs,
+	path::PathBuf,
+	thread,
+	time::{Duration, SystemTime},
+};
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+/// How often watched files get checked
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DesktopFileChange {
+	/// The file was changed or (re)created, parsed again
+	Modified(DesktopFile),
+	Deleted,
+}
+
+/// Changes of a watched desktop file, watching stops when this is dropped
+pub struct ChangeStream {
+	receiver: mpsc::UnboundedReceiver<DesktopFileChange>,
+}
+impl ChangeStream {
+	pub async fn next(&mut self) -> Option<DesktopFileChange> {
+		self.receiver.recv().await
+	}
+	/// The next change if there's one already, without waiting (eg: once per frame)
+	pub fn try_next(&mut self) -> Option<DesktopFileChange> {
+		match self.receiver.try_recv() {
+			Ok(change) => Some(change),
+			Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+		}
+	}
+}
+
+/// Watches a single desktop file (eg: a pinned application) for changes and deletion
+pub fn watch_desktop_file(path: impl Into<PathBuf>) -> ChangeStream {
+	watch_desktop_file_every(path.into(), WATCH_INTERVAL)
+}
+
+fn watch_desktop_file_every(path: PathBuf, interval: Duration) -> ChangeStream {
+	// Modification time and length, the length catches changes within the mtime granularity
+	let snapshot = |path: &PathBuf| -> Option<(SystemTime, u64)> {
+		let metadata = fs::metadata(path).ok()?;
+		Some((metadata.modified().ok()?, metadata.len()))
+	};
+	let (sender, receiver) = mpsc::unbounded_channel();
+	thread::spawn(move || {
+		let mut last = snapshot(&path);
+		while !sender.is_closed() {
+			thread::sleep(interval);
+			let current = snapshot(&path);
+			if current == last {
+				continue;
+			}
+			let change = match current {
+				Some(_) => match parse_desktop_file(path.clone()) {
+					Ok(desktop_file) => DesktopFileChange::Modified(desktop_file),
+					// Probably still being written, try again next time
+					Err(_) => continue,
+				},
+				None => DesktopFileChange::Deleted,
+			};
+			last = current;
+			if sender.send(change).is_err() {
+				break;
+			}
+		}
+	});
+	ChangeStream { receiver }
+}
+
+#[test]
+fn test_watch_desktop_file() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let path = dir.path().join("pinned.desktop");
+	fs::write(&path, "[Desktop Entry]\nName=Pinned\nExec=pinned").unwrap();
+
+	let mut changes = watch_desktop_file_every(path.clone(), Duration::from_millis(10));
+	let mut wait_for_change = || {
+		for _ in 0..200 {
+			if let Some(change) = changes.try_next() {
+				return change;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		panic!("no change seen");
+	};
+
+	fs::write(&path, "[Desktop Entry]\nName=Pinned again\nExec=pinned").unwrap();
+	let DesktopFileChange::Modified(desktop_file) = wait_for_change() else {
+		panic!("expected a modification")
+	};
+	assert_eq!(desktop_file.name.as_deref(), Some("Pinned again"));
+
+	fs::remove_file(&path).unwrap();
+	assert_eq!(wait_for_change(), DesktopFileChange::Deleted);
+}
@@ -1,3 +1,4 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use color_eyre::eyre::Result;
 use lazy_static::lazy_static;
 use linicon;
@@ -8,7 +9,7 @@ use resvg::usvg::{FitTo, Tree};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use serde_with::serde_as;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::fs::create_dir_all;
 use std::fs::File;
@@ -16,12 +17,19 @@ use std::io::{BufRead, BufReader, ErrorKind};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use std::{env, fs};
+use tracing::warn;
+
+use crate::application::Application;
+use crate::color::Rgba;
+use crate::exec::{find_terminal, strip_field_codes, tokenize_exec, FieldCode};
 
 use walkdir::WalkDir;
 #[serde_as]
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct ImageCache {
 	path: PathBuf,
 	#[serde_as(as = "Vec<(_, _)>")]
@@ -57,6 +65,148 @@ impl ImageCache {
 	}
 }
 
+static MAX_ICON_SIZE: AtomicU16 = AtomicU16::new(1024);
+
+/// Caps the size icons get rasterized at, no matter the size requested, to bound texture memory
+pub fn set_max_icon_size(max_size: u16) {
+	MAX_ICON_SIZE.store(max_size, Ordering::Relaxed);
+}
+
+fn clamp_icon_size(size: u16) -> u16 {
+	size.min(MAX_ICON_SIZE.load(Ordering::Relaxed))
+}
+
+/// How icons are colored when they get rasterized
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IconColorMode {
+	#[default]
+	FullColor,
+	/// SVGs are drawn entirely in this color and raster images are desaturated, for a uniform look
+	Monochrome(Rgba),
+}
+impl IconColorMode {
+	/// Keeps rasterized icons of different modes apart in the icon cache
+	fn cache_suffix(self) -> String {
+		match self {
+			IconColorMode::FullColor => String::new(),
+			IconColorMode::Monochrome(color) => {
+				let [r, g, b, a] = color.to_array().map(|c| (c * 255.0).round() as u8);
+				format!("-mono-{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+			}
+		}
+	}
+}
+
+/// How `Icon::cached_process_with` renders icons, with the color mode and corner radius of `resolver`
+fn icon_render_options(resolver: &IconResolver) -> SvgOptions {
+	SvgOptions {
+		color_mode: resolver.color_mode,
+		corner_radius: resolver.corner_radius,
+		..Default::default()
+	}
+}
+
+#[test]
+fn test_icon_render_options() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let png_path = dir.path().join("colorful.png");
+	image::RgbaImage::from_pixel(8, 8, image::Rgba([230, 50, 60, 255]))
+		.save(&png_path)
+		.unwrap();
+	let icon = Icon::from_path(png_path.clone(), 8).unwrap();
+
+	// PNG icons are used as they are, unless the resolver recolors them
+	let full_color = IconResolver::new().cache_dir(dir.path().join("full"));
+	let icon_path =
+		|resolver: &IconResolver| icon.clone().cached_process_with(resolver, 8).unwrap().path;
+	assert_eq!(icon_path(&full_color), png_path);
+	let monochrome = IconResolver::new()
+		.cache_dir(dir.path().join("mono"))
+		.color_mode(IconColorMode::Monochrome(Rgba::WHITE));
+	let gray = icon_path(&monochrome);
+	assert!(gray.starts_with(dir.path().join("mono")));
+	assert!(gray.to_string_lossy().ends_with("-8-mono-ffffffff.png"));
+
+	let rounded = IconResolver::new()
+		.cache_dir(dir.path().join("rounded"))
+		.corner_radius(0.25);
+	let rounded_path = icon_path(&rounded);
+	assert!(rounded_path.starts_with(dir.path().join("rounded")));
+	assert!(rounded_path.to_string_lossy().ends_with("-8-round250.png"));
+	// Out of range radii are clamped
+	assert_eq!(IconResolver::new().corner_radius(2.0).corner_radius, 0.5);
+}
+
+/// Keeps rasterized icons with different corner radii apart in the icon cache
+fn corner_radius_cache_suffix(radius: f32) -> String {
+	if radius > 0.0 {
+		format!("-round{}", (radius * 1000.0).round() as u32)
+	} else {
+		String::new()
+	}
+}
+
+/// How much of the pixel at `x`,`y` of a `width`x`height` rectangle is inside the rectangle with
+/// its corners rounded by `radius` pixels, antialiased over a pixel
+fn rounded_corner_coverage(x: u32, y: u32, width: u32, height: u32, radius: f32) -> f32 {
+	let (width, height) = (width as f32, height as f32);
+	let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+	// Distance past the inner rectangle the corners are centered on, 0 along the straight edges
+	let dx = (radius - x).max(x - (width - radius)).max(0.0);
+	let dy = (radius - y).max(y - (height - radius)).max(0.0);
+	(radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0)
+}
+
+/// Masks the corners of RGBA8 `pixels` out, all channels are scaled if they're premultiplied.
+/// `radius` is a fraction of the shorter side.
+fn mask_rounded_corners(
+	pixels: &mut [u8],
+	width: u32,
+	height: u32,
+	radius: f32,
+	premultiplied: bool,
+) {
+	let radius = radius * width.min(height) as f32;
+	for (i, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+		let (x, y) = (i as u32 % width, i as u32 / width);
+		let coverage = rounded_corner_coverage(x, y, width, height, radius);
+		if coverage >= 1.0 {
+			continue;
+		}
+		let channels = if premultiplied {
+			&mut pixel[..]
+		} else {
+			&mut pixel[3..]
+		};
+		for channel in channels {
+			*channel = (*channel as f32 * coverage).round() as u8;
+		}
+	}
+}
+
+/// How far away tiles are usually looked at, in meters
+pub const DEFAULT_VIEWING_DISTANCE: f32 = 0.5;
+/// Angular resolution of a typical headset display
+pub const DEFAULT_PIXELS_PER_DEGREE: f32 = 20.0;
+
+/// The icon size, in pixels, that matches how many pixels a tile `tile_size` meters wide covers
+/// on a display with `pixels_per_degree` when looked at from `viewing_distance` meters away
+pub fn icon_size_for_tile(tile_size: f32, viewing_distance: f32, pixels_per_degree: f32) -> u16 {
+	let degrees = (2.0 * (tile_size / 2.0).atan2(viewing_distance)).to_degrees();
+	(degrees * pixels_per_degree)
+		.ceil()
+		.clamp(1.0, u16::MAX as f32) as u16
+}
+
+#[test]
+fn test_icon_size_for_tile() {
+	// 6cm at 50cm is about 6.87 degrees
+	assert_eq!(icon_size_for_tile(0.06, 0.5, 20.0), 138);
+	// Twice as far away covers about half the pixels
+	assert_eq!(icon_size_for_tile(0.06, 1.0, 20.0), 69);
+	assert_eq!(icon_size_for_tile(0.0, 0.5, 20.0), 1);
+}
+
 lazy_static! {
 	static ref IMAGE_CACHE: Mutex<ImageCache> = Mutex::new(ImageCache::new(
 		get_image_cache_dir().join("imagechache.map")
@@ -64,54 +214,88 @@ lazy_static! {
 }
 
 fn get_data_dirs() -> Vec<PathBuf> {
+	system_data_dirs()
+		.into_iter()
+		.chain([data_home()])
+		.filter(|dir| dir.exists() && dir.is_dir())
+		.collect()
+}
+
+fn system_data_dirs() -> Vec<PathBuf> {
 	let xdg_data_dirs_str = std::env::var("XDG_DATA_DIRS").unwrap_or_default();
 
-	let xdg_data_dirs = xdg_data_dirs_str
+	xdg_data_dirs_str
 		.split(":")
-		.filter_map(|dir| PathBuf::from_str(dir).ok());
+		.filter_map(|dir| PathBuf::from_str(dir).ok())
+		.collect()
+}
 
-	let data_home = dirs::home_dir()
-		.unwrap_or(PathBuf::from_str("/usr/share/").expect(
+/// $XDG_DATA_HOME, or ~/.local/share
+fn data_home() -> PathBuf {
+	dirs::data_dir().unwrap_or(
+		PathBuf::from_str("/usr/share/").expect(
 			"No XDG_DATA_DIR set, no HOME directory found and no /usr/share direcotry found",
-		))
-		.join(".local")
-		.join("share");
-
-	xdg_data_dirs
-		.chain([data_home].into_iter())
-		.filter(|dir| dir.exists() && dir.is_dir())
-		.collect()
+		),
+	)
 }
 
 fn get_app_dirs() -> Vec<PathBuf> {
-	get_data_dirs()
+	// The user's applications take precedence over the system ones when discovering
+	[data_home()]
 		.into_iter()
+		.chain(system_data_dirs())
 		.map(|dir| dir.join("applications"))
 		.filter(|dir| dir.exists() && dir.is_dir())
 		.collect()
 }
 
 pub fn get_desktop_files() -> Vec<PathBuf> {
-	let desktop_extension = OsString::from_str("desktop").unwrap();
 	// Get the list of directories to search
-	let app_dirs = get_app_dirs();
+	get_desktop_files_in(get_app_dirs(), true)
+}
+
+fn get_desktop_files_in(app_dirs: Vec<PathBuf>, follow_symlinks: bool) -> Vec<PathBuf> {
+	let desktop_extension = OsString::from_str("desktop").unwrap();
 	app_dirs
 		.into_iter()
 		.flat_map(|dir| {
-			// Follow symlinks and recursively search directories
+			// Recursively search directories
 			WalkDir::new(dir)
-				.follow_links(true)
+				.follow_links(follow_symlinks)
 				.into_iter()
 				.filter_map(|entry| entry.ok())
-				.filter(|entry| entry.file_type().is_file())
+				.filter(|entry| entry.path().extension() == Some(&desktop_extension))
+				.filter(|entry| {
+					if entry.file_type().is_dir() {
+						warn!("Skipping {}, it's a directory", entry.path().display());
+					}
+					entry.file_type().is_file()
+				})
 				.map(|entry| entry.path().to_path_buf())
 		})
-		.filter(|path| path.extension() == Some(&desktop_extension))
 		.collect::<Vec<PathBuf>>()
 }
 
+#[test]
+fn test_get_desktop_files_skips_directories() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	fs::create_dir_all(dir.path().join("odd.desktop")).unwrap();
+	fs::write(
+		dir.path().join("real.desktop"),
+		"[Desktop Entry]\nName=Real\nExec=real",
+	)
+	.unwrap();
+
+	let desktop_files = get_desktop_files_in(vec![dir.path().to_path_buf()], true);
+	assert_eq!(desktop_files, vec![dir.path().join("real.desktop")]);
+	assert!(parse_desktop_file(dir.path().join("odd.desktop"))
+		.unwrap_err()
+		.contains("directory"));
+}
+
 #[test]
 fn test_get_desktop_files() {
+	let _env = crate::test_utils::lock_env();
 	let desktop_files = get_desktop_files();
 	dbg!(&desktop_files);
 	assert!(desktop_files
@@ -120,6 +304,9 @@ fn test_get_desktop_files() {
 }
 
 pub fn parse_desktop_file(path: PathBuf) -> Result<DesktopFile, String> {
+	if path.is_dir() {
+		return Err(format!("{} is a directory", path.display()));
+	}
 	// Open the file in read-only mode
 	let file = match fs::File::open(
 		env::current_dir()
@@ -129,144 +316,1508 @@ pub fn parse_desktop_file(path: PathBuf) -> Result<DesktopFile, String> {
 		Ok(file) => file,
 		Err(err) => return Err(format!("Failed to open file: {}", err)),
 	};
+	let modified = file.metadata().and_then(|m| m.modified()).ok();
+
+	let mut desktop_file = DesktopFile::from_reader(BufReader::new(file), path)?;
+	desktop_file.modified = modified;
+	Ok(desktop_file)
+}
+
+impl DesktopFile {
+	/// Parses a desktop entry that isn't necessarily on disk, eg: one extracted from an archive,
+	/// `logical_path` is where it would be and gives the desktop ID
+	pub fn from_bytes(bytes: &[u8], logical_path: impl Into<PathBuf>) -> Result<Self, String> {
+		DesktopFile::from_reader(bytes, logical_path.into())
+	}
+
+	/// When a key is repeated within a group only its first occurrence counts, as the spec says
+	pub fn from_reader(reader: impl BufRead, path: PathBuf) -> Result<Self, String> {
+		// Create temporary variables to hold the parsed values
+		let mut name = None;
+		let mut generic_name = None;
+		let mut keywords = Vec::new();
+		let mut command = None;
+		let mut categories = Vec::new();
+		let mut mime_types = Vec::new();
+		let mut icon = None;
+		let mut no_display = false;
+		let mut single_main_window = false;
+		let mut startup_wm_class = None;
+		let mut startup_notify = false;
+		let mut prefers_non_default_gpu = false;
+		let mut terminal = false;
+		let mut multiple_args = false;
+		let mut try_exec = None;
+		let mut entry_type = None;
+		let mut action_ids = Vec::new();
+		let mut action_groups: Vec<DesktopAction> = Vec::new();
+		let mut extra = HashMap::new();
+		let mut desktop_entry_found = false;
+		let mut in_action_group = false;
+		let mut seen_keys = HashSet::new();
+		let mut entries = Vec::new();
+
+		let re = Regex::new(r"^\[([^\]]*)\]$").unwrap();
+
+		// Loop through each line of the file, legacy files aren't always valid UTF-8 so decode lossily
+		for line in reader.split(b'\n') {
+			let line = match line {
+				Ok(line) => String::from_utf8_lossy(&line)
+					.trim_end_matches('\r')
+					.to_string(),
+				Err(err) => return Err(format!("Failed to read line: {}", err)),
+			};
+
+			// Skip empty lines and lines that start with "#" (comments)
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			if let Some(captures) = re.captures(&line) {
+				let group = captures.get(1).unwrap().as_str();
+				// Only the main group, not other groups that happen to mention it (eg: KDE overrides)
+				desktop_entry_found = group == "Desktop Entry" || group == "KDE Desktop Entry";
+				in_action_group = match group.strip_prefix("Desktop Action ") {
+					Some(id) => {
+						action_groups.push(DesktopAction {
+							id: id.to_string(),
+							..Default::default()
+						});
+						true
+					}
+					None => false,
+				};
+				seen_keys.clear();
+				continue;
+			}
+
+			if in_action_group {
+				let (Some((key, value)), Some(action)) =
+					(line.split_once('='), action_groups.last_mut())
+				else {
+					continue;
+				};
+				if !seen_keys.insert(key.to_string()) {
+					continue;
+				}
+				match key {
+					"Name" => action.name = Some(value.to_string()),
+					"Exec" => action.command = Some(value.to_string()),
+					"Icon" => action.icon = Some(value.to_string()),
+					_ => (),
+				}
+				continue;
+			}
+
+			if !desktop_entry_found {
+				continue;
+			}
+			// Split the line into a key-value pair by looking for the first "=" character
+			let parts = line.split_once('=');
+			let (key, value) = match parts {
+				Some((key, value)) => (key, value),
+				None => continue,
+			};
+			if !seen_keys.insert(key.to_string()) {
+				continue;
+			}
+			entries.push((key.to_string(), value.to_string()));
+		}
+
+		// Entries that only set the vendor form of a key get the standard one too
+		for (vendor_key, standard_key) in VENDOR_KEY_ALIASES {
+			if entries.iter().any(|(key, _)| key == standard_key) {
+				continue;
+			}
+			let vendor_value = entries
+				.iter()
+				.find(|(key, _)| key == vendor_key)
+				.map(|(_, value)| value.clone());
+			if let Some(value) = vendor_value {
+				entries.push((standard_key.to_string(), value));
+			}
+		}
+
+		for (key, value) in entries {
+			let value = value.as_str();
+			// Parse the key-value pair based on the key
+			match key.as_str() {
+				"Name" => name = Some(value.to_string()),
+				"GenericName" => generic_name = Some(value.to_string()),
+				"Keywords" => {
+					keywords = value
+						.split(';')
+						.map(|s| s.to_string())
+						.filter(|s| !s.is_empty())
+						.collect()
+				}
+				"Type" => entry_type = Some(value.to_string()),
+				"Exec" => command = Some(value.to_string()),
+				"TryExec" => try_exec = Some(value.to_string()),
+				"Categories" => {
+					categories = value
+						.split(';')
+						.map(|s| s.to_string())
+						.filter(|s| !s.is_empty())
+						.collect()
+				}
+				"MimeType" => {
+					mime_types = value
+						.split(';')
+						.map(|s| s.to_string())
+						.filter(|s| !s.is_empty())
+						.collect()
+				}
+				"Icon" => icon = Some(value.to_string()),
+				"Actions" => {
+					action_ids = value
+						.split(';')
+						.map(|s| s.to_string())
+						.filter(|s| !s.is_empty())
+						.collect()
+				}
+				"NoDisplay" => {
+					no_display = match value {
+						"true" => true,
+						_ => false,
+					}
+				}
+				"StartupWMClass" => startup_wm_class = Some(value.to_string()),
+				"SingleMainWindow" => {
+					single_main_window = match value {
+						"true" => true,
+						_ => false,
+					}
+				}
+				"StartupNotify" => {
+					startup_notify = match value {
+						"true" => true,
+						_ => false,
+					}
+				}
+				"PrefersNonDefaultGPU" => {
+					prefers_non_default_gpu = match value {
+						"true" => true,
+						_ => false,
+					}
+				}
+				"Terminal" => {
+					terminal = match value {
+						"true" => true,
+						_ => false,
+					}
+				}
+				"MultipleArgs" => {
+					multiple_args = match value {
+						"true" => true,
+						_ => false,
+					}
+				}
+				// Keep unknown and deprecated keys (eg: Encoding) around for callers
+				_ => {
+					extra.insert(key, value.to_string());
+				}
+			}
+		}
 
-	let reader = BufReader::new(file);
+		// In the order `Actions` lists them rather than the order of the groups in the file, so menus
+		// show them as the author intended. Action groups that aren't listed are ignored, as the spec says.
+		let actions = action_ids
+			.iter()
+			.filter_map(|id| {
+				let index = action_groups.iter().position(|action| action.id == *id)?;
+				Some(action_groups.swap_remove(index))
+			})
+			.collect();
+
+		// Create and return a new DesktopFile instance with the parsed values
+		Ok(DesktopFile {
+			path,
+			modified: None,
+			name,
+			generic_name,
+			keywords,
+			entry_type,
+			command,
+			try_exec,
+			categories,
+			mime_types,
+			icon,
+			no_display,
+			single_main_window,
+			startup_wm_class,
+			startup_notify,
+			prefers_non_default_gpu,
+			terminal,
+			multiple_args,
+			actions,
+			extra,
+		})
+	}
+}
 
-	// Create temporary variables to hold the parsed values
-	let mut name = None;
-	let mut command = None;
-	let mut categories = Vec::new();
-	let mut icon = None;
-	let mut no_display = false;
-	let mut desktop_entry_found = false;
+#[test]
+fn test_desktop_file_from_bytes() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Portable\nExec=AppRun %F\nIcon=portable\n",
+		"squashfs-root/portable.desktop",
+	)
+	.unwrap();
+	assert_eq!(desktop_file.id(), "portable.desktop");
+	assert_eq!(desktop_file.name.as_deref(), Some("Portable"));
+	assert_eq!(desktop_file.command.as_deref(), Some("AppRun %F"));
+}
+
+#[test]
+fn test_duplicate_keys_first_wins() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=First\nExec=first\nName=Second\nExec=second\n",
+		"duplicate.desktop",
+	)
+	.unwrap();
+	assert_eq!(desktop_file.name.as_deref(), Some("First"));
+	assert_eq!(desktop_file.command.as_deref(), Some("first"));
+}
+
+/// Keys the spec deprecated, they are still parsed into `DesktopFile::extra`
+pub const DEPRECATED_KEYS: [&str; 11] = [
+	"Encoding",
+	"MiniIcon",
+	"TerminalOptions",
+	"Protocols",
+	"Extensions",
+	"BinaryPattern",
+	"MapNotify",
+	"SwallowTitle",
+	"SwallowExec",
+	"SortOrder",
+	"FilePattern",
+];
+
+/// Something wrong with a desktop file that didn't keep it from being parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DesktopFileWarning {
+	/// No `Type` key, the spec requires one
+	MissingType,
+	/// No `Name` key, the spec requires one
+	MissingName,
+	/// An application without `Exec` that isn't D-Bus activatable either, so it can't be launched
+	MissingExec,
+	/// One of `DEPRECATED_KEYS`
+	DeprecatedKey(String),
+	/// A field code in `Exec` the spec doesn't define, eg: `%x`
+	UnknownFieldCode(String),
+}
+impl std::fmt::Display for DesktopFileWarning {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			DesktopFileWarning::MissingType => write!(f, "missing Type key"),
+			DesktopFileWarning::MissingName => write!(f, "missing Name key"),
+			DesktopFileWarning::MissingExec => write!(f, "application without Exec key"),
+			DesktopFileWarning::DeprecatedKey(key) => write!(f, "deprecated key {}", key),
+			DesktopFileWarning::UnknownFieldCode(code) => {
+				write!(f, "unknown field code {} in Exec", code)
+			}
+		}
+	}
+}
+
+/// Problems of a parsed desktop file that don't keep protostar from using it but that its
+/// author (or an admin) should fix. Empty if there's nothing to complain about.
+pub fn validate_desktop_file(desktop_file: &DesktopFile) -> Vec<DesktopFileWarning> {
+	let mut warnings = Vec::new();
+	if desktop_file.entry_type.is_none() {
+		warnings.push(DesktopFileWarning::MissingType);
+	}
+	if desktop_file.name.is_none() {
+		warnings.push(DesktopFileWarning::MissingName);
+	}
+	let dbus_activatable = desktop_file
+		.extra
+		.get("DBusActivatable")
+		.map(String::as_str)
+		== Some("true");
+	if desktop_file.is_application() && desktop_file.command.is_none() && !dbus_activatable {
+		warnings.push(DesktopFileWarning::MissingExec);
+	}
+	warnings.extend(
+		DEPRECATED_KEYS
+			.iter()
+			.filter(|key| desktop_file.extra.contains_key(**key))
+			.map(|key| DesktopFileWarning::DeprecatedKey(key.to_string())),
+	);
+	if let Some(command) = &desktop_file.command {
+		for arg in tokenize_exec(command) {
+			let mut chars = arg.chars();
+			while let Some(c) = chars.next() {
+				if c != '%' {
+					continue;
+				}
+				match chars.next() {
+					Some('%') | None => (),
+					Some(code) if FieldCode::from_char(code).is_some() => (),
+					Some(code) => {
+						warnings.push(DesktopFileWarning::UnknownFieldCode(format!("%{}", code)))
+					}
+				}
+			}
+		}
+	}
+	warnings
+}
+
+#[test]
+fn test_validate_desktop_file() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nType=Application\nName=Fine\nExec=fine %U\n",
+		"fine.desktop",
+	)
+	.unwrap();
+	assert!(validate_desktop_file(&desktop_file).is_empty());
+
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nEncoding=UTF-8\nMiniIcon=mini\nExec=sloppy %x 100%% %f\n",
+		"sloppy.desktop",
+	)
+	.unwrap();
+	assert_eq!(
+		validate_desktop_file(&desktop_file),
+		[
+			DesktopFileWarning::MissingType,
+			DesktopFileWarning::MissingName,
+			DesktopFileWarning::DeprecatedKey("Encoding".into()),
+			DesktopFileWarning::DeprecatedKey("MiniIcon".into()),
+			DesktopFileWarning::UnknownFieldCode("%x".into()),
+		]
+	);
+
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nType=Application\nName=Broken\n",
+		"broken.desktop",
+	)
+	.unwrap();
+	assert_eq!(
+		validate_desktop_file(&desktop_file),
+		[DesktopFileWarning::MissingExec]
+	);
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nType=Application\nName=Service\nDBusActivatable=true\n",
+		"org.example.Service.desktop",
+	)
+	.unwrap();
+	assert!(validate_desktop_file(&desktop_file).is_empty());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DedupMode {
+	/// Only the highest precedence desktop file of each desktop ID is kept
+	#[default]
+	Id,
+	/// Entries running the exact same command are also collapsed into the highest precedence one
+	IdAndExec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TerminalApps {
+	#[default]
+	Keep,
+	/// Drop `Terminal=true` applications when there's no terminal emulator to run them in
+	IfTerminalAvailable,
+}
+
+/// What `discover_applications` looks for and where
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+	root_dirs: Option<Vec<PathBuf>>,
+	follow_symlinks: bool,
+	dedup: DedupMode,
+	terminal_apps: TerminalApps,
+	blocklist: HashSet<String>,
+	excluded_categories: HashSet<String>,
+}
+impl Default for DiscoveryConfig {
+	fn default() -> Self {
+		DiscoveryConfig {
+			root_dirs: None,
+			follow_symlinks: true,
+			dedup: DedupMode::default(),
+			terminal_apps: TerminalApps::default(),
+			blocklist: HashSet::new(),
+			excluded_categories: HashSet::new(),
+		}
+	}
+}
+impl DiscoveryConfig {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Directories of desktop files searched instead of the `applications` dirs of the XDG data dirs,
+	/// eg: for apps shipped in a nonstandard prefix. Earlier dirs take precedence.
+	pub fn root_dirs(mut self, root_dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+		self.root_dirs = Some(root_dirs.into_iter().collect());
+		self
+	}
+	/// Whether symlinked desktop files and directories are followed, they are by default
+	pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+		self.follow_symlinks = follow_symlinks;
+		self
+	}
+	pub fn dedup(mut self, dedup: DedupMode) -> Self {
+		self.dedup = dedup;
+		self
+	}
+	pub fn terminal_apps(mut self, terminal_apps: TerminalApps) -> Self {
+		self.terminal_apps = terminal_apps;
+		self
+	}
+	/// Desktop IDs to skip, eg: from `config::load_blocklist`
+	pub fn blocklist(mut self, blocklist: HashSet<String>) -> Self {
+		self.blocklist = blocklist;
+		self
+	}
+	/// Skip applications in any of these categories
+	pub fn excluded_categories(mut self, excluded_categories: HashSet<String>) -> Self {
+		self.excluded_categories = excluded_categories;
+		self
+	}
+}
+
+/// Parses every desktop file on the system, skipping hidden applications and the ones `config` filters out
+pub fn discover_applications(config: &DiscoveryConfig) -> Vec<DesktopFile> {
+	discover_applications_in(config, find_terminal().is_some())
+}
+
+fn discover_applications_in(
+	config: &DiscoveryConfig,
+	terminal_available: bool,
+) -> Vec<DesktopFile> {
+	let drop_terminal_apps =
+		config.terminal_apps == TerminalApps::IfTerminalAvailable && !terminal_available;
+	let app_dirs = config.root_dirs.clone().unwrap_or_else(get_app_dirs);
+	let mut ids = HashSet::new();
+	let mut commands = HashSet::new();
+	get_desktop_files_in(app_dirs, config.follow_symlinks)
+		.into_iter()
+		.filter_map(|path| parse_desktop_file(path).ok())
+		// Deduplicate IDs before filtering so a hidden override also hides what it overrides
+		.filter(|d| ids.insert(d.id()))
+		.filter(|d| d.is_application())
+		.filter(|d| !d.no_display)
+		.filter(|d| !config.blocklist.contains(&d.id()))
+		.filter(|d| {
+			!d.categories
+				.iter()
+				.any(|c| config.excluded_categories.contains(c))
+		})
+		.filter(|d| !(drop_terminal_apps && d.terminal))
+		.filter(|d| match (config.dedup, &d.command) {
+			(DedupMode::IdAndExec, Some(command)) => commands.insert(command_key(command)),
+			_ => true,
+		})
+		.collect()
+}
+
+/// The arguments of an `Exec` value without its field codes, so equivalent commands compare equal
+fn command_key(command: &str) -> Vec<String> {
+	tokenize_exec(command)
+		.iter()
+		.filter_map(|arg| strip_field_codes(arg))
+		.collect()
+}
+
+#[test]
+fn test_discover_applications_blocklist() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	fs::write(
+		dir.path().join("kept.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Kept\nExec=kept",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("blocked.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Blocked\nExec=blocked",
+	)
+	.unwrap();
+
+	let config = DiscoveryConfig::new()
+		.root_dirs([dir.path().to_path_buf()])
+		.blocklist(HashSet::from(["blocked.desktop".to_string()]));
+	let apps = discover_applications_in(&config, true);
+	assert_eq!(apps.len(), 1);
+	assert_eq!(apps[0].id(), "kept.desktop");
+}
+
+#[test]
+fn test_discover_applications_excluded_categories() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	fs::write(
+		dir.path().join("settings.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Settings\nExec=settings\nCategories=GTK;Settings;",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\nCategories=Utility;TextEditor;",
+	)
+	.unwrap();
+
+	let config = DiscoveryConfig::new()
+		.root_dirs([dir.path().to_path_buf()])
+		.excluded_categories(HashSet::from(["Settings".to_string()]));
+	let apps = discover_applications_in(&config, true);
+	assert_eq!(apps.len(), 1);
+	assert_eq!(apps[0].id(), "editor.desktop");
+}
+
+#[test]
+fn test_discover_applications_drops_terminal_apps() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	fs::write(
+		dir.path().join("htop.desktop"),
+		"[Desktop Entry]\nType=Application\nName=htop\nExec=htop\nTerminal=true",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\nTerminal=false",
+	)
+	.unwrap();
+
+	let config = DiscoveryConfig::new().root_dirs([dir.path().to_path_buf()]);
+	assert_eq!(discover_applications_in(&config, false).len(), 2);
+	let config = config.terminal_apps(TerminalApps::IfTerminalAvailable);
+	assert_eq!(discover_applications_in(&config, true).len(), 2);
+	let apps = discover_applications_in(&config, false);
+	assert_eq!(apps.len(), 1);
+	assert_eq!(apps[0].id(), "editor.desktop");
+}
+
+#[test]
+fn test_discover_applications_only_applications() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	fs::write(
+		dir.path().join("compress.desktop"),
+		"[Desktop Entry]\nType=Service\nX-KDE-ServiceTypes=KonqPopupMenu/Plugin\nMimeType=all/allfiles;\nActions=compress;\n\n\
+		[Desktop Action compress]\nName=Compress\nExec=ark --add %F\n\n\
+		[X-KDE Desktop Entry Overrides]\nName=Garbage\nExec=garbage\n",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("website.desktop"),
+		"[Desktop Entry]\nType=Link\nName=Website\nURL=https://example.com",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("typeless.desktop"),
+		"[Desktop Entry]\nName=Typeless\nExec=typeless",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Editor\nExec=editor",
+	)
+	.unwrap();
+
+	let compress = parse_desktop_file(dir.path().join("compress.desktop")).unwrap();
+	assert_eq!(compress.name, None);
+	assert_eq!(compress.command, None);
+	assert!(!compress.is_application());
+	let typeless = parse_desktop_file(dir.path().join("typeless.desktop")).unwrap();
+	assert!(!typeless.is_application());
+
+	let config = DiscoveryConfig::new().root_dirs([dir.path().to_path_buf()]);
+	let apps = discover_applications_in(&config, true);
+	assert_eq!(apps.len(), 1);
+	assert_eq!(apps[0].id(), "editor.desktop");
+}
+
+#[test]
+fn test_discover_applications_dedup_exec() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	fs::write(
+		dir.path().join("org.example.Editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Editor\nExec=editor %F",
+	)
+	.unwrap();
+	fs::write(
+		dir.path().join("editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Editor (legacy)\nExec=editor %U",
+	)
+	.unwrap();
+
+	let config = DiscoveryConfig::new().root_dirs([dir.path().to_path_buf()]);
+	assert_eq!(discover_applications_in(&config, true).len(), 2);
+	let config = config.dedup(DedupMode::IdAndExec);
+	assert_eq!(discover_applications_in(&config, true).len(), 1);
+}
+
+#[test]
+fn test_discovery_config() {
+	use std::os::unix::fs::symlink;
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let prefix = dir.path().join("opt/apps");
+	let overrides = dir.path().join("overrides");
+	let shared = dir.path().join("shared");
+	for dir in [&prefix, &overrides, &shared] {
+		fs::create_dir_all(dir).unwrap();
+	}
+	let write = |path: PathBuf, contents: &str| fs::write(path, contents).unwrap();
+	write(
+		prefix.join("editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Editor\nExec=editor %F",
+	);
+	write(
+		overrides.join("editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Custom Editor\nExec=editor --custom",
+	);
+	write(
+		prefix.join("editor-legacy.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Legacy Editor\nExec=editor %U",
+	);
+	write(
+		prefix.join("custom-editor.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Duplicate\nExec=editor --custom %f",
+	);
+	write(
+		prefix.join("game.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Game\nExec=game\nCategories=Game;",
+	);
+	write(
+		prefix.join("blocked.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Blocked\nExec=blocked",
+	);
+	write(
+		prefix.join("top.desktop"),
+		"[Desktop Entry]\nType=Application\nName=top\nExec=top\nTerminal=true",
+	);
+	write(
+		shared.join("linked.desktop"),
+		"[Desktop Entry]\nType=Application\nName=Linked\nExec=linked",
+	);
+	symlink(shared.join("linked.desktop"), prefix.join("linked.desktop")).unwrap();
+
+	let config = DiscoveryConfig::new()
+		.root_dirs([overrides, prefix])
+		.follow_symlinks(false)
+		.dedup(DedupMode::IdAndExec)
+		.blocklist(HashSet::from(["blocked.desktop".to_string()]))
+		.excluded_categories(HashSet::from(["Game".to_string()]));
+	// Without a terminal emulator around
+	let names = |config: &DiscoveryConfig| {
+		let mut names: Vec<_> = discover_applications_in(config, false)
+			.into_iter()
+			.map(|d| d.name.unwrap())
+			.collect();
+		names.sort();
+		names
+	};
+	// The override replaces the entry with the same ID and collapses the one running the same command
+	assert_eq!(
+		names(&config.clone().terminal_apps(TerminalApps::Keep)),
+		["Custom Editor", "Legacy Editor", "top"]
+	);
+	let config = config.terminal_apps(TerminalApps::IfTerminalAvailable);
+	assert_eq!(names(&config), ["Custom Editor", "Legacy Editor"]);
+
+	assert!(names(&config.follow_symlinks(true)).contains(&"Linked".to_string()));
+}
+
+#[test]
+fn test_parse_desktop_file() {
+	// Create a temporary directory and a test desktop file
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let file = dir.path().join("test.desktop");
+	let data = "[Desktop Entry]\nName=Test\nExec=test\nCategories=A;B;C\nIcon=test.png";
+	fs::write(&file, data).unwrap();
+
+	// Parse the test desktop file
+	let desktop_file = parse_desktop_file(file).unwrap();
+
+	// Check the parsed values
+	assert_eq!(desktop_file.name, Some("Test".to_string()));
+	assert_eq!(desktop_file.command, Some("test".to_string()));
+	assert_eq!(
+		desktop_file.categories,
+		vec!["A".to_string(), "B".to_string(), "C".to_string()]
+	);
+	assert_eq!(desktop_file.icon, Some("test.png".to_string()));
+}
+
+#[test]
+fn test_parse_legacy_desktop_file() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let file = dir.path().join("legacy.desktop");
+	let mut data =
+		b"[KDE Desktop Entry]\nEncoding=Legacy-Mixed\nName=Legacy\nExec=legacy\nComment=Caf"
+			.to_vec();
+	// Latin-1 encoded "é", not valid UTF-8
+	data.extend_from_slice(b"\xe9\nSwallowTitle=legacy\n");
+	fs::write(&file, data).unwrap();
+
+	let desktop_file = parse_desktop_file(file).unwrap();
+	assert_eq!(desktop_file.name, Some("Legacy".to_string()));
+	assert_eq!(desktop_file.command, Some("legacy".to_string()));
+	assert_eq!(
+		desktop_file.extra.get("Encoding").map(String::as_str),
+		Some("Legacy-Mixed")
+	);
+	assert!(desktop_file.extra.contains_key("SwallowTitle"));
+	assert!(desktop_file.extra["Comment"].starts_with("Caf"));
+}
+
+#[test]
+fn test_parse_single_main_window() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let file = dir.path().join("single.desktop");
+	fs::write(
+		&file,
+		"[Desktop Entry]\nName=Single\nExec=single\nSingleMainWindow=true",
+	)
+	.unwrap();
+
+	assert!(parse_desktop_file(file).unwrap().single_main_window);
+}
+
+/// Vendor keys and the standard key they stand in for, used when an entry only sets the vendor one
+const VENDOR_KEY_ALIASES: [(&str, &str); 3] = [
+	("X-KDE-StartupNotify", "StartupNotify"),
+	("X-GNOME-SingleWindow", "SingleMainWindow"),
+	("X-MultipleArgs", "MultipleArgs"),
+];
+
+#[test]
+fn test_vendor_key_aliases() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Konsole\nExec=konsole\nX-KDE-StartupNotify=true\n",
+		PathBuf::from("org.kde.konsole.desktop"),
+	)
+	.unwrap();
+	assert!(desktop_file.startup_notify);
+	assert_eq!(
+		desktop_file
+			.extra
+			.get("X-KDE-StartupNotify")
+			.map(String::as_str),
+		Some("true")
+	);
+
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Both\nX-KDE-StartupNotify=true\nStartupNotify=false\n",
+		PathBuf::from("both.desktop"),
+	)
+	.unwrap();
+	assert!(!desktop_file.startup_notify);
+
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Player\nExec=player %F\nX-MultipleArgs=true\n",
+		PathBuf::from("player.desktop"),
+	)
+	.unwrap();
+	assert!(desktop_file.multiple_args);
+	assert!(
+		!DesktopFile::from_bytes(
+			b"[Desktop Entry]\nName=Player\nExec=player %f\n",
+			PathBuf::from("player.desktop"),
+		)
+		.unwrap()
+		.multiple_args
+	);
+}
+
+/// The locale messages are shown in, from `LC_ALL`, `LC_MESSAGES` or `LANG`
+pub fn env_locale() -> Option<String> {
+	["LC_ALL", "LC_MESSAGES", "LANG"]
+		.iter()
+		.filter_map(|var| env::var(var).ok())
+		.find(|locale| !locale.is_empty())
+		.filter(|locale| locale != "C" && locale != "POSIX")
+}
+
+/// The `[locale]` suffixes that match `locale`, most specific first, as the desktop entry spec says.
+/// The encoding (eg: `.UTF-8`) is never part of them.
+fn locale_candidates(locale: &str) -> Vec<String> {
+	let (locale, modifier) = match locale.split_once('@') {
+		Some((locale, modifier)) => (locale, Some(modifier)),
+		None => (locale, None),
+	};
+	let locale = locale.split('.').next().unwrap_or(locale);
+	let (lang, country) = match locale.split_once('_') {
+		Some((lang, country)) => (lang, Some(country)),
+		None => (locale, None),
+	};
+
+	let mut candidates = Vec::new();
+	if let (Some(country), Some(modifier)) = (country, modifier) {
+		candidates.push(format!("{}_{}@{}", lang, country, modifier));
+	}
+	if let Some(country) = country {
+		candidates.push(format!("{}_{}", lang, country));
+	}
+	if let Some(modifier) = modifier {
+		candidates.push(format!("{}@{}", lang, modifier));
+	}
+	candidates.push(lang.to_string());
+	candidates
+}
+
+#[test]
+fn test_localized_name() {
+	assert_eq!(
+		locale_candidates("sr_YU.UTF-8@Latn"),
+		["sr_YU@Latn", "sr_YU", "sr@Latn", "sr"]
+	);
+	assert_eq!(locale_candidates("de"), ["de"]);
+
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Files\nName[de]=Dateien\nName[pt_BR]=Arquivos\nExec=files\n",
+		PathBuf::from("files.desktop"),
+	)
+	.unwrap();
+	assert_eq!(
+		desktop_file.localized_name(Some("en_US.UTF-8")),
+		Some("Files")
+	);
+	assert_eq!(desktop_file.localized_name(Some("de")), Some("Dateien"));
+	assert_eq!(
+		desktop_file.localized_name(Some("de_AT.UTF-8")),
+		Some("Dateien")
+	);
+	assert_eq!(desktop_file.localized_name(Some("pt_BR")), Some("Arquivos"));
+	assert_eq!(desktop_file.localized_name(Some("pt_PT")), Some("Files"));
+}
+
+/// Vendor keys with a longer name than `Name`, in order of preference
+const FULL_NAME_KEYS: [&str; 2] = ["X-GNOME-FullName", "X-KDE-FullName"];
+
+/// Keys applications put their own version in, in order of preference
+const APP_VERSION_KEYS: [&str; 4] = [
+	"X-AppVersion",
+	"X-AppImage-Version",
+	"X-KDE-PluginInfo-Version",
+	"X-GNOME-Bugzilla-Version",
+];
+
+/// A `[Desktop Action ...]` group
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopAction {
+	/// The part of the group name after `Desktop Action`, as listed in `Actions`
+	pub id: String,
+	pub name: Option<String>,
+	pub command: Option<String>,
+	pub icon: Option<String>,
+}
+
+#[test]
+fn test_desktop_actions() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Browser\nExec=browser\nActions=new-window;private;\n\n\
+		[Desktop Action new-window]\nName=New Window\nExec=browser --new-window\n\n\
+		[Desktop Action private]\nName=New Private Window\nExec=browser --private\nIcon=browser-private\n\n\
+		[Desktop Action unlisted]\nName=Unlisted\nExec=browser --unlisted\n",
+		PathBuf::from("browser.desktop"),
+	)
+	.unwrap();
+	assert_eq!(desktop_file.name.as_deref(), Some("Browser"));
+	assert_eq!(desktop_file.command.as_deref(), Some("browser"));
+	let ids: Vec<_> = desktop_file.actions.iter().map(|a| a.id.as_str()).collect();
+	assert_eq!(ids, ["new-window", "private"]);
+	assert_eq!(
+		desktop_file.actions[1],
+		DesktopAction {
+			id: "private".into(),
+			name: Some("New Private Window".into()),
+			command: Some("browser --private".into()),
+			icon: Some("browser-private".into()),
+		}
+	);
+	assert!(!desktop_file.extra.contains_key("Actions"));
+
+	// The groups come in another order than `Actions` lists them
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nName=Editor\nExec=editor\nActions=open;new;settings;open;\n\n\
+		[Desktop Action settings]\nName=Settings\nExec=editor --settings\n\n\
+		[Desktop Action new]\nName=New\nExec=editor --new\n\n\
+		[Desktop Action open]\nName=Open\nExec=editor --open\n",
+		PathBuf::from("editor.desktop"),
+	)
+	.unwrap();
+	let ids: Vec<_> = desktop_file.actions.iter().map(|a| a.id.as_str()).collect();
+	assert_eq!(ids, ["open", "new", "settings"]);
+}
+
+// Missing fields fall back to their defaults so older parse caches stay readable
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopFile {
+	path: PathBuf,
+	modified: Option<SystemTime>,
+	pub name: Option<String>,
+	/// What kind of application it is, eg: `Web Browser`
+	pub generic_name: Option<String>,
+	/// Extra words to search the application by
+	pub keywords: Vec<String>,
+	/// `Application`, `Link` or `Directory`, or something else for non-standard entries (eg: KDE services)
+	pub entry_type: Option<String>,
+	pub command: Option<String>,
+	/// A program that has to exist for the application to be runnable
+	pub try_exec: Option<String>,
+	pub categories: Vec<String>,
+	/// MIME types the application can open, eg: `audio/mpeg`
+	pub mime_types: Vec<String>,
+	pub icon: Option<String>,
+	pub no_display: bool,
+	/// The application never opens more than one main window, so a launcher can raise it instead of launching again
+	pub single_main_window: bool,
+	pub startup_wm_class: Option<String>,
+	/// The application tells the launcher when it has started, see the startup notification spec
+	pub startup_notify: bool,
+	/// The application should run on the discrete GPU of hybrid graphics systems
+	pub prefers_non_default_gpu: bool,
+	/// The application has to be run in a terminal emulator
+	pub terminal: bool,
+	/// The application takes several files at once, from the legacy `MultipleArgs` key
+	pub multiple_args: bool,
+	/// Extra things the application can do from the launcher, eg: open a private window, in the order `Actions` lists them
+	pub actions: Vec<DesktopAction>,
+	/// Every key of the entry protostar doesn't handle itself
+	pub extra: HashMap<String, String>,
+}
+impl DesktopFile {
+	/// The desktop file ID, eg: `firefox.desktop`
+	pub fn id(&self) -> String {
+		self.path
+			.file_name()
+			.map(|name| name.to_string_lossy().into_owned())
+			.unwrap_or_default()
+	}
+
+	/// When the file was last modified (eg: installed or updated), if it was read from disk
+	pub fn modified(&self) -> Option<SystemTime> {
+		self.modified
+	}
+
+	/// The version of the application itself (not of the desktop entry spec), from the first
+	/// vendor key that has one
+	pub fn app_version(&self) -> Option<&str> {
+		APP_VERSION_KEYS
+			.iter()
+			.find_map(|key| self.extra.get(*key))
+			.map(String::as_str)
+	}
+
+	/// Whether this describes an application, entries without a `Type` don't, as the spec requires it
+	pub fn is_application(&self) -> bool {
+		self.entry_type.as_deref() == Some("Application")
+	}
+
+	/// `Name` in `locale` (eg: `de_DE.UTF-8`), or in the environment's locale if it's `None`,
+	/// falling back to the untranslated name
+	pub fn localized_name(&self, locale: Option<&str>) -> Option<&str> {
+		self.localized_value("Name", self.name.as_deref(), locale)
+	}
+	/// Same as `localized_name` for the longer name some vendors set next to `Name`
+	/// (eg: `X-GNOME-FullName`), `None` if there isn't one
+	pub fn localized_full_name(&self, locale: Option<&str>) -> Option<&str> {
+		FULL_NAME_KEYS.iter().find_map(|key| {
+			let full_name = self.extra.get(*key).map(String::as_str);
+			self.localized_value(key, full_name, locale)
+		})
+	}
+	/// Same as `localized_name` for `GenericName`
+	pub fn localized_generic_name(&self, locale: Option<&str>) -> Option<&str> {
+		self.localized_value("GenericName", self.generic_name.as_deref(), locale)
+	}
+	/// Same as `localized_name` for `Comment`, the tooltip of the application
+	pub fn localized_comment(&self, locale: Option<&str>) -> Option<&str> {
+		let comment = self.extra.get("Comment").map(String::as_str);
+		self.localized_value("Comment", comment, locale)
+	}
+
+	fn localized_value<'a>(
+		&'a self,
+		key: &str,
+		untranslated: Option<&'a str>,
+		locale: Option<&str>,
+	) -> Option<&'a str> {
+		let locale = locale.map(str::to_string).or_else(env_locale);
+		locale
+			.iter()
+			.flat_map(|locale| locale_candidates(locale))
+			.find_map(|locale| self.extra.get(&format!("{}[{}]", key, locale)))
+			.map(String::as_str)
+			.or(untranslated)
+	}
+
+	/// The current contents of the file this was parsed from, read again from disk
+	pub fn raw_contents(&self) -> std::io::Result<String> {
+		Ok(String::from_utf8_lossy(&fs::read(&self.path)?).into_owned())
+	}
+
+	pub fn get_raw_icons(&self, preferred_px_size: u16) -> Vec<Icon> {
+		self.get_raw_icons_with(&IconResolver::default(), preferred_px_size)
+	}
+	/// Like `get_raw_icons`, looked up the way `resolver` is set up to
+	pub fn get_raw_icons_with(&self, resolver: &IconResolver, preferred_px_size: u16) -> Vec<Icon> {
+		// Get the name of the icon from the DesktopFile struct
+		let Some(icon_name) = self.icon.as_ref() else { return Vec::new(); };
+		if let Some(data) = icon_name.strip_prefix("data:") {
+			return icon_from_data_uri(data, preferred_px_size, &resolver.image_cache_dir())
+				.into_iter()
+				.collect();
+		}
+		let test_icon_path = self.path.join(Path::new(icon_name));
+		if test_icon_path.exists() {
+			if let Some(icon) = Icon::from_path(test_icon_path, preferred_px_size) {
+				return vec![icon];
+			}
+		}
+
+		let cache_key = resolver.cache_key(icon_name);
+		if let Some(cache_icon_path) = resolver.image_cache().lock().unwrap().map.get(&cache_key) {
+			if cache_icon_path.exists() {
+				if let Some(icon) = Icon::from_path(cache_icon_path.to_owned(), preferred_px_size) {
+					return vec![icon];
+				}
+			}
+		}
+
+		let theme = resolver.theme();
+		let icons = lookup_theme_icons(&theme, icon_name);
+		if icons.is_empty() {
+			// Some entries use a theme relative path (eg: `apps/firefox`), retry with just the name
+			if let Some((_, basename)) = icon_name.rsplit_once('/') {
+				if !basename.is_empty() {
+					return lookup_theme_icons(&theme, basename);
+				}
+			}
+		}
+		icons
+	}
+}
+
+/// Decodes the icon embedded in a `data:` URI (without the `data:`) to the icon cache in `cache_dir`
+fn icon_from_data_uri(data: &str, preferred_px_size: u16, cache_dir: &Path) -> Option<Icon> {
+	use std::hash::{Hash, Hasher};
+	let (header, payload) = data.split_once(',')?;
+	let extension = match header.strip_suffix(";base64")? {
+		"image/png" => "png",
+		"image/svg+xml" => "svg",
+		"image/webp" => "webp",
+		_ => return None,
+	};
+	let bytes = BASE64.decode(payload.trim()).ok()?;
+
+	let mut hasher = std::collections::hash_map::DefaultHasher::new();
+	bytes.hash(&mut hasher);
+	let path = cache_dir.join(format!("data-{:x}.{}", hasher.finish(), extension));
+	if !path.exists() {
+		fs::write(&path, &bytes).ok()?;
+	}
+	Icon::from_path(path, preferred_px_size)
+}
+
+#[test]
+fn test_icon_from_data_uri() {
+	use image::GenericImageView;
+	let desktop_file = DesktopFile {
+		icon: Some("data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGP4z8DwHwAFAAH/iZk9HQAAAABJRU5ErkJggg==".into()),
+		..Default::default()
+	};
+	let icons = desktop_file.get_raw_icons(64);
+	assert_eq!(icons.len(), 1);
+	assert_eq!(icons[0].icon_type, IconType::Png);
+	assert_eq!(image::open(&icons[0].path).unwrap().dimensions(), (1, 1));
+
+	let not_an_image = DesktopFile {
+		icon: Some("data:text/plain;base64,aGVsbG8=".into()),
+		..Default::default()
+	};
+	assert!(not_an_image.get_raw_icons(64).is_empty());
+}
+
+/// How applications' icons are resolved, for choices that belong to a launcher and not to the
+/// whole process
+#[derive(Debug, Clone, Default)]
+pub struct IconResolver {
+	/// Theme icons are looked up in instead of the system one
+	theme: Option<String>,
+	category_icon_fallback: bool,
+	/// Its own icon cache, the one of the process in `get_image_cache_dir` if `None`
+	cache: Option<Arc<Mutex<ImageCache>>>,
+	color_mode: IconColorMode,
+	corner_radius: f32,
+}
+impl IconResolver {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Looks icons up in `theme` instead of the system icon theme from now on, `None` goes back to the system one.
+	/// Tiles keep the icon they have until `ProtoStar::refresh_icon`.
+	pub fn set_theme(&mut self, theme: Option<String>) {
+		self.theme = theme;
+	}
+	/// The theme icons are looked up in, the one given to `set_theme` or the system one
+	pub fn theme(&self) -> String {
+		self.theme
+			.clone()
+			.or_else(linicon::get_system_theme)
+			.unwrap_or_else(|| "hicolor".to_string())
+	}
+	/// Gives applications that don't declare an icon the standard icon of their main category
+	/// (eg: `applications-development`), so they don't look broken. Off by default.
+	pub fn category_icon_fallback(mut self, enabled: bool) -> Self {
+		self.category_icon_fallback = enabled;
+		self
+	}
+	pub fn uses_category_icon_fallback(&self) -> bool {
+		self.category_icon_fallback
+	}
+	/// How icons are colored when they get rasterized, PNG icons get rasterized too when it's not `FullColor`
+	pub fn color_mode(mut self, color_mode: IconColorMode) -> Self {
+		self.color_mode = color_mode;
+		self
+	}
+	/// Rounds the corners of icons when they get rasterized, for flat tiles without a custom model.
+	/// `corner_radius` is a fraction of the icon size, from `0.0` (square) to `0.5` (round).
+	/// PNG icons get rasterized too when it's not `0.0`.
+	pub fn corner_radius(mut self, corner_radius: f32) -> Self {
+		self.corner_radius = corner_radius.clamp(0.0, 0.5);
+		self
+	}
+	/// Remembers and renders icons in `cache_dir` instead of in the icon cache of the process,
+	/// eg: to keep the icons of a launcher apart
+	pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+		let cache_dir = cache_dir.into();
+		let _ = create_dir_all(&cache_dir);
+		self.cache = Some(Arc::new(Mutex::new(ImageCache::new(
+			cache_dir.join("imagechache.map"),
+		))));
+		self
+	}
+
+	fn image_cache(&self) -> &Mutex<ImageCache> {
+		self.cache.as_deref().unwrap_or(&*IMAGE_CACHE)
+	}
+	/// Icons are remembered by name, the ones found in a theme set with `set_theme` are kept apart
+	/// from the ones of the system theme
+	fn cache_key(&self, icon_name: &str) -> String {
+		match &self.theme {
+			Some(theme) => format!("{}:{}", theme, icon_name),
+			None => icon_name.to_string(),
+		}
+	}
+	fn image_cache_dir(&self) -> PathBuf {
+		match &self.cache {
+			Some(cache) => cache.lock().unwrap().path.parent().unwrap().to_path_buf(),
+			None => get_image_cache_dir(),
+		}
+	}
+
+	pub fn icon_for_category(&self, category: &str, preferred_px_size: u16) -> Option<Icon> {
+		lookup_theme_icons(&self.theme(), category_icon_name(category)?)
+			.into_iter()
+			.max_by_key(|i| i.size)
+			.and_then(|i| i.cached_process_with(self, preferred_px_size).ok())
+	}
+
+	/// Disk space taken in the icon cache by the PNGs rendered from `icon` (the value of an `Icon` key), in bytes
+	pub fn icon_cache_size(&self, icon: &str) -> u64 {
+		// Same keys as `cached_process`, by file stem for icons given as a path
+		let key = if Path::new(icon).is_absolute() {
+			Path::new(icon)
+				.with_extension("")
+				.file_name()
+				.map(|name| name.to_string_lossy().into_owned())
+				.unwrap_or_default()
+		} else {
+			icon.to_string()
+		};
+		let key = self.cache_key(&key);
+		let Some(source) = self.image_cache().lock().unwrap().map.get(&key).cloned() else {
+			return 0;
+		};
+		cached_size_in(&self.image_cache_dir(), &source)
+	}
+	/// Disk space taken in the icon cache by the PNGs rendered from the icons of `app`, its own
+	/// and the ones of its actions, in bytes
+	pub fn app_cache_size(&self, app: &Application) -> u64 {
+		let icons: HashSet<&str> = app
+			.icon_name()
+			.into_iter()
+			.chain(
+				app.actions()
+					.iter()
+					.filter_map(|action| action.icon.as_deref()),
+			)
+			.collect();
+		icons
+			.into_iter()
+			.map(|icon| self.icon_cache_size(icon))
+			.sum()
+	}
+}
+
+#[test]
+fn test_app_cache_size() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg = |name: &str| {
+		let path = dir.path().join(name);
+		fs::write(
+			&path,
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><circle cx=\"5\" cy=\"5\" r=\"5\" fill=\"#e34\"/></svg>",
+		)
+		.unwrap();
+		path.to_string_lossy().into_owned()
+	};
+	let app = |icon: String, action_icon: Option<String>| {
+		Application::create(DesktopFile {
+			icon: Some(icon),
+			actions: vec![DesktopAction {
+				id: "new-window".into(),
+				icon: action_icon,
+				..Default::default()
+			}],
+			..Default::default()
+		})
+		.unwrap()
+	};
+	let painter = app(svg("painter.svg"), Some(svg("painter-new.svg")));
+	let viewer = app(svg("viewer.svg"), None);
+	let resolver = IconResolver::new().cache_dir(dir.path().join("cache"));
+	assert_eq!(resolver.app_cache_size(&painter), 0);
+
+	let rendered_size = |icon: Icon, size: u16| {
+		let rendered = icon.cached_process_with(&resolver, size).unwrap();
+		fs::metadata(rendered.path).unwrap().len()
+	};
+	let icon = |path: &str| Icon::from_path(PathBuf::from(path), 32).unwrap();
+	let expected = rendered_size(icon(painter.icon_name().unwrap()), 32)
+		+ rendered_size(icon(painter.icon_name().unwrap()), 64)
+		+ rendered_size(icon(painter.actions()[0].icon.as_deref().unwrap()), 32);
+	rendered_size(icon(viewer.icon_name().unwrap()), 32);
+	assert_eq!(resolver.app_cache_size(&painter), expected);
+	// Only in the cache of the resolver that rendered them
+	assert_eq!(
+		IconResolver::new()
+			.cache_dir(dir.path().join("other"))
+			.app_cache_size(&painter),
+		0
+	);
+}
+
+fn lookup_theme_icons(theme: &str, icon_name: &str) -> Vec<Icon> {
+	theme_inheritance_chain(theme)
+		.into_iter()
+		.map(|theme| {
+			linicon::lookup_icon(icon_name)
+				.from_theme(theme)
+				.use_fallback_themes(false)
+				.filter_map(|i| i.ok())
+				.filter(|i| i.icon_type != linicon::IconType::XMP) //TODO: support XMP
+				.map(|i| Icon::from_path(i.path, i.max_size - 2).unwrap())
+				.collect::<Vec<Icon>>()
+		})
+		.find(|icons| !icons.is_empty())
+		.unwrap_or_default()
+}
+
+/// The standard theme icon for a main category, from the freedesktop icon naming spec
+pub fn category_icon_name(category: &str) -> Option<&'static str> {
+	Some(match category {
+		"AudioVideo" | "Audio" | "Video" => "applications-multimedia",
+		"Development" => "applications-development",
+		"Education" | "Science" => "applications-science",
+		"Game" => "applications-games",
+		"Graphics" => "applications-graphics",
+		"Network" => "applications-internet",
+		"Office" => "applications-office",
+		"Settings" => "preferences-desktop",
+		"System" => "applications-system",
+		"Utility" => "applications-utilities",
+		_ => return None,
+	})
+}
+
+pub fn icon_for_category(category: &str, preferred_px_size: u16) -> Option<Icon> {
+	IconResolver::default().icon_for_category(category, preferred_px_size)
+}
+
+#[test]
+fn test_icon_for_category() {
+	assert_eq!(
+		category_icon_name("Development"),
+		Some("applications-development")
+	);
+	assert_eq!(category_icon_name("X-Unknown"), None);
+
+	let xdg = crate::test_utils::FakeXdg::new();
+	xdg.add_icon_theme("hicolor", &[], &["32x32/apps"]);
+	let icon = xdg.add_icon("hicolor", "32x32/apps", "applications-development.png", &[]);
+	let mut resolver = IconResolver::new().cache_dir(xdg.cache_home());
+	resolver.set_theme(Some("hicolor".to_string()));
+	assert_eq!(
+		resolver
+			.icon_for_category("Development", 32)
+			.map(|i| i.path),
+		Some(icon)
+	);
+	assert!(resolver.icon_for_category("X-Unknown", 32).is_none());
+}
 
-	let re = Regex::new(r"^\[([^\]]*)\]$").unwrap();
+fn get_icon_dirs() -> Vec<PathBuf> {
+	dirs::home_dir()
+		.map(|home| home.join(".icons"))
+		.into_iter()
+		.chain(get_data_dirs().into_iter().map(|dir| dir.join("icons")))
+		.filter(|dir| dir.is_dir())
+		.collect()
+}
 
-	// Loop through each line of the file
-	for line in reader.lines() {
-		let line = match line {
-			Ok(line) => line,
-			Err(err) => return Err(format!("Failed to read line: {}", err)),
-		};
+fn read_theme_inherits(icon_dirs: &[PathBuf], theme: &str) -> Vec<String> {
+	let Some(index) = icon_dirs
+		.iter()
+		.map(|dir| dir.join(theme).join("index.theme"))
+		.find(|index| index.is_file()) else { return Vec::new(); };
+	let Ok(contents) = fs::read_to_string(index) else { return Vec::new(); };
+	contents
+		.lines()
+		.find_map(|line| line.strip_prefix("Inherits="))
+		.map(|inherits| {
+			inherits
+				.split(',')
+				.map(|theme| theme.trim().to_string())
+				.filter(|theme| !theme.is_empty())
+				.collect()
+		})
+		.unwrap_or_default()
+}
 
-		// Skip empty lines and lines that start with "#" (comments)
-		if line.is_empty() || line.starts_with('#') {
-			continue;
-		}
+/// The order icon themes should be searched in: `theme`, then the themes it inherits from, then hicolor
+pub fn theme_inheritance_chain(theme: &str) -> Vec<String> {
+	theme_inheritance_chain_in(&get_icon_dirs(), theme)
+}
 
-		if let Some(captures) = re.captures(&line) {
-			let entry = captures.get(1).unwrap();
-			desktop_entry_found = entry.as_str().contains("Desktop Entry");
+fn theme_inheritance_chain_in(icon_dirs: &[PathBuf], theme: &str) -> Vec<String> {
+	fn visit(
+		icon_dirs: &[PathBuf],
+		theme: String,
+		path: &mut Vec<String>,
+		chain: &mut Vec<String>,
+	) {
+		if path.contains(&theme) {
+			warn!(
+				"Icon theme {} inherits from itself, falling back to hicolor",
+				theme
+			);
+			return;
 		}
-
-		if !desktop_entry_found {
-			continue;
+		// hicolor always goes last
+		if theme == "hicolor" || chain.contains(&theme) {
+			return;
 		}
-		// Split the line into a key-value pair by looking for the first "=" character
-		let parts = line.split_once('=');
-		let (key, value) = match parts {
-			Some((key, value)) => (key, value),
-			None => continue,
-		};
-
-		// Parse the key-value pair based on the key
-		match key {
-			"Name" => name = Some(value.to_string()),
-			"Exec" => command = Some(value.to_string()),
-			"Categories" => {
-				categories = value
-					.split(';')
-					.map(|s| s.to_string())
-					.filter(|s| !s.is_empty())
-					.collect()
-			}
-			"Icon" => icon = Some(value.to_string()),
-			"NoDisplay" => {
-				no_display = match value {
-					"true" => true,
-					_ => false,
-				}
-			}
-			_ => (), // Ignore unknown keys
+		let inherits = read_theme_inherits(icon_dirs, &theme);
+		chain.push(theme.clone());
+		path.push(theme);
+		for parent in inherits {
+			visit(icon_dirs, parent, path, chain);
 		}
+		path.pop();
 	}
 
-	// Create and return a new DesktopFile instance with the parsed values
-	Ok(DesktopFile {
-		path,
-		name,
-		command,
-		categories,
-		icon,
-		no_display,
-	})
+	let mut chain = Vec::new();
+	visit(icon_dirs, theme.to_string(), &mut Vec::new(), &mut chain);
+	chain.push("hicolor".to_string());
+	chain
 }
 
 #[test]
-fn test_parse_desktop_file() {
-	// Create a temporary directory and a test desktop file
+fn test_theme_inheritance_cycle() {
 	let dir = tempdir::TempDir::new("test").unwrap();
-	let file = dir.path().join("test.desktop");
-	let data = "[Desktop Entry]\nName=Test\nExec=test\nCategories=A;B;C\nIcon=test.png";
-	fs::write(&file, data).unwrap();
+	for (theme, inherits) in [("A", "B"), ("B", "A")] {
+		let theme_dir = dir.path().join(theme);
+		fs::create_dir_all(&theme_dir).unwrap();
+		fs::write(
+			theme_dir.join("index.theme"),
+			format!("[Icon Theme]\nName={theme}\nInherits={inherits}\n"),
+		)
+		.unwrap();
+	}
 
-	// Parse the test desktop file
-	let desktop_file = parse_desktop_file(file).unwrap();
+	let chain = theme_inheritance_chain_in(&[dir.path().to_path_buf()], "A");
+	assert_eq!(chain, vec!["A", "B", "hicolor"]);
+}
 
-	// Check the parsed values
-	assert_eq!(desktop_file.name, Some("Test".to_string()));
-	assert_eq!(desktop_file.command, Some("test".to_string()));
-	assert_eq!(
-		desktop_file.categories,
-		vec!["A".to_string(), "B".to_string(), "C".to_string()]
-	);
-	assert_eq!(desktop_file.icon, Some("test.png".to_string()));
+#[test]
+fn test_app_version() {
+	let desktop_file = DesktopFile::from_bytes(
+		b"[Desktop Entry]\nVersion=1.5\nName=Versioned\nX-AppVersion=1.2.3\n",
+		"versioned.desktop",
+	)
+	.unwrap();
+	assert_eq!(desktop_file.app_version(), Some("1.2.3"));
+	assert_eq!(DesktopFile::default().app_version(), None);
 }
 
-#[derive(Debug, Clone)]
-pub struct DesktopFile {
-	path: PathBuf,
-	pub name: Option<String>,
-	pub command: Option<String>,
-	pub categories: Vec<String>,
-	pub icon: Option<String>,
-	pub no_display: bool,
+#[test]
+fn test_raw_contents() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let path = dir.path().join("raw.desktop");
+	let contents = "[Desktop Entry]\n# A comment\nName=Raw\nExec=raw\n";
+	fs::write(&path, contents).unwrap();
+	let desktop_file = parse_desktop_file(path).unwrap();
+	assert_eq!(desktop_file.raw_contents().unwrap(), contents);
 }
-impl DesktopFile {
-	pub fn get_raw_icons(&self, preferred_px_size: u16) -> Vec<Icon> {
-		// Get the name of the icon from the DesktopFile struct
-		let Some(icon_name) = self.icon.as_ref() else { return Vec::new(); };
-		let test_icon_path = self.path.join(Path::new(icon_name));
-		if test_icon_path.exists() {
-			if let Some(icon) = Icon::from_path(test_icon_path, preferred_px_size) {
-				return vec![icon];
-			}
-		}
 
-		if let Some(cache_icon_path) = IMAGE_CACHE.lock().unwrap().map.get(icon_name) {
-			if cache_icon_path.exists() {
-				if let Some(icon) = Icon::from_path(cache_icon_path.to_owned(), preferred_px_size) {
-					return vec![icon];
-				}
-			}
-		}
+#[test]
+fn test_desktop_file_serde_round_trip() {
+	let desktop_file = DesktopFile {
+		path: PathBuf::from("/usr/share/applications/test.desktop"),
+		modified: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_680_000_000)),
+		name: Some("Test".into()),
+		generic_name: Some("Tester".into()),
+		keywords: vec!["testing".into()],
+		entry_type: Some("Application".into()),
+		command: Some("test --flag".into()),
+		try_exec: Some("test".into()),
+		categories: vec!["A".into(), "B".into()],
+		mime_types: vec!["text/plain".into()],
+		icon: Some("test".into()),
+		no_display: true,
+		single_main_window: true,
+		startup_wm_class: Some("Test".into()),
+		startup_notify: true,
+		prefers_non_default_gpu: true,
+		terminal: true,
+		multiple_args: true,
+		actions: vec![DesktopAction {
+			id: "new-window".into(),
+			name: Some("New Window".into()),
+			command: Some("test --new-window".into()),
+			icon: None,
+		}],
+		extra: HashMap::from([("Encoding".to_string(), "UTF-8".to_string())]),
+	};
 
-		let mut icons_iter = linicon::lookup_icon(icon_name)
-			.use_fallback_themes(false)
-			.peekable();
+	let json = serde_json::to_string(&desktop_file).unwrap();
+	let parsed: DesktopFile = serde_json::from_str(&json).unwrap();
+	assert_eq!(parsed, desktop_file);
+}
 
-		if icons_iter.peek().is_none() {
-			//dbg!("No icons found in current theme");
-			icons_iter = linicon::lookup_icon(icon_name).peekable();
-		}
+#[test]
+fn test_get_icon_theme_subpath() {
+	let xdg = crate::test_utils::FakeXdg::new();
+	xdg.add_icon_theme("hicolor", &[], &["32x32/apps"]);
+	let icon = xdg.add_icon("hicolor", "32x32/apps", "browser.png", &[]);
+	let mut resolver = IconResolver::new().cache_dir(xdg.cache_home());
+	resolver.set_theme(Some("hicolor".to_string()));
+	let desktop_file = DesktopFile {
+		icon: Some("apps/browser".into()),
+		..Default::default()
+	};
 
-		let sized_png: Vec<Icon> = icons_iter
-			.filter_map(|i| i.ok())
-			.filter(|i| i.icon_type != linicon::IconType::XMP) //TODO: support XMP
-			.map(|i| Icon::from_path(i.path, i.max_size - 2).unwrap())
-			.collect();
-		sized_png
-	}
+	let icons = desktop_file.get_raw_icons_with(&resolver, 32);
+	assert!(icons.iter().any(|i| i.path == icon));
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -276,18 +1827,90 @@ pub struct Icon {
 	pub size: u16,
 }
 
+/// Why an application has no icon to show
+#[derive(Debug)]
+pub enum IconError {
+	/// The desktop file has no `Icon` key
+	NotDeclared,
+	/// Nothing matches the `Icon` key in the icon themes
+	NotFound,
+	/// The icon was found but couldn't be turned into something the server can load
+	RenderFailed(std::io::Error),
+}
+impl std::fmt::Display for IconError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			IconError::NotDeclared => write!(f, "no icon declared"),
+			IconError::NotFound => write!(f, "icon not found"),
+			IconError::RenderFailed(err) => write!(f, "icon failed to render: {}", err),
+		}
+	}
+}
+impl std::error::Error for IconError {}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum IconType {
 	Png,
 	Svg,
+	Webp,
 	Gltf,
 }
+impl IconType {
+	/// The type of an image from its first bytes, `None` if it's not recognizable (eg: glTF, which is plain JSON)
+	pub fn sniff(header: &[u8]) -> Option<IconType> {
+		if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+			return Some(IconType::Png);
+		}
+		if header.starts_with(b"RIFF") && header.get(8..12) == Some(&b"WEBP"[..]) {
+			return Some(IconType::Webp);
+		}
+		if header.starts_with(b"glTF") {
+			return Some(IconType::Gltf);
+		}
+		let text = String::from_utf8_lossy(header);
+		let text = text.trim_start_matches('\u{feff}').trim_start();
+		if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+			return Some(IconType::Svg);
+		}
+		None
+	}
+}
+
+static SNIFF_ICON_TYPES: AtomicBool = AtomicBool::new(false);
+
+/// Makes `Icon::from_path` tell the type of icons from their content instead of their extension,
+/// eg: for a `.png` that's actually an SVG. Off by default since every icon has to be opened.
+pub fn set_sniff_icon_types(enabled: bool) {
+	SNIFF_ICON_TYPES.store(enabled, Ordering::Relaxed);
+}
+
+/// The first bytes of the file at `path`, enough for `IconType::sniff`
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+	let mut header = Vec::with_capacity(512);
+	File::open(path)
+		.ok()?
+		.take(512)
+		.read_to_end(&mut header)
+		.ok()?;
+	Some(header)
+}
+
 impl Icon {
 	pub fn from_path(path: PathBuf, size: u16) -> Option<Icon> {
-		let icon_type = match path.extension().and_then(|ext| ext.to_str()) {
-			Some("png") => IconType::Png,
-			Some("svg") => IconType::Svg,
-			Some("glb") | Some("gltf") => IconType::Gltf,
+		Icon::from_path_sniffing(path, size, SNIFF_ICON_TYPES.load(Ordering::Relaxed))
+	}
+	/// Like `from_path`, with the type told from the content of the file when `sniff` is true,
+	/// whatever `set_sniff_icon_types` was given
+	pub fn from_path_sniffing(path: PathBuf, size: u16, sniff: bool) -> Option<Icon> {
+		let sniffed = sniff
+			.then(|| IconType::sniff(&read_header(&path)?))
+			.flatten();
+		let icon_type = match (sniffed, path.extension().and_then(|ext| ext.to_str())) {
+			(Some(icon_type), _) => icon_type,
+			(None, Some("png")) => IconType::Png,
+			(None, Some("svg")) => IconType::Svg,
+			(None, Some("webp")) => IconType::Webp,
+			(None, Some("glb") | Some("gltf")) => IconType::Gltf,
 			_ => return None,
 		};
 		return Some(Icon {
@@ -298,38 +1921,115 @@ impl Icon {
 	}
 
 	pub fn cached_process(self, size: u16) -> Result<Icon, std::io::Error> {
-		if !IMAGE_CACHE.lock().unwrap().map.contains_key(
-			&self
-				.path
+		self.cached_process_with(&IconResolver::default(), size)
+	}
+	/// Like `cached_process`, in the icon cache of `resolver`
+	pub fn cached_process_with(
+		self,
+		resolver: &IconResolver,
+		size: u16,
+	) -> Result<Icon, std::io::Error> {
+		let size = clamp_icon_size(size);
+		let cache_key = resolver.cache_key(
+			self.path
 				.with_extension("")
 				.file_name()
 				.unwrap()
 				.to_str()
-				.unwrap()
-				.to_owned(),
-		) {
+				.unwrap(),
+		);
+		let image_cache = resolver.image_cache();
+		if !image_cache.lock().unwrap().map.contains_key(&cache_key) {
 			dbg!("Saving value in the DB");
-			IMAGE_CACHE.lock().unwrap().insert(
-				self.path
-					.with_extension("")
-					.file_name()
-					.unwrap()
-					.to_str()
-					.unwrap()
-					.to_owned(),
-				self.path.clone(),
-			);
-			IMAGE_CACHE.lock().unwrap().save();
+			image_cache
+				.lock()
+				.unwrap()
+				.insert(cache_key, self.path.clone());
+			image_cache.lock().unwrap().save();
 		}
+		let options = icon_render_options(resolver);
+		let cache_dir = resolver.image_cache_dir();
 		match self.icon_type {
-			IconType::Svg => Ok(Icon::from_path(get_png_from_svg(self.path, size)?, size).unwrap()),
+			IconType::Svg => Ok(Icon::from_path(
+				get_png_from_svg_in(&cache_dir, &self.path, size, &options)?,
+				size,
+			)
+			.unwrap()),
+			IconType::Webp => Ok(Icon::from_path(
+				get_png_from_raster_in(&cache_dir, &self.path, size, &options)?,
+				size,
+			)
+			.unwrap()),
+			IconType::Png
+				if options.color_mode != IconColorMode::FullColor
+					|| options.corner_radius > 0.0 =>
+			{
+				Ok(Icon::from_path(
+					get_png_from_raster_in(&cache_dir, &self.path, size, &options)?,
+					size,
+				)
+				.unwrap())
+			}
 			_ => Ok(self),
 		}
 	}
 }
 
+#[test]
+fn test_sniff_icon_types() {
+	assert_eq!(
+		IconType::sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+		Some(IconType::Png)
+	);
+	assert_eq!(
+		IconType::sniff(b"RIFF\x24\0\0\0WEBPVP8 "),
+		Some(IconType::Webp)
+	);
+	assert_eq!(IconType::sniff(b"glTF\x02\0\0\0"), Some(IconType::Gltf));
+	assert_eq!(
+		IconType::sniff(b"\xef\xbb\xbf\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+		Some(IconType::Svg)
+	);
+	assert_eq!(
+		IconType::sniff(b"<?xml version=\"1.0\"?>\n<!-- drawn by hand -->\n<svg/>"),
+		Some(IconType::Svg)
+	);
+	assert_eq!(IconType::sniff(b"{\"asset\": {}}"), None);
+	assert_eq!(IconType::sniff(b""), None);
+
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let mislabeled = dir.path().join("mislabeled-sniffed.png");
+	fs::write(
+		&mislabeled,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"10\" height=\"10\"/></svg>",
+	)
+	.unwrap();
+	let unknown = dir.path().join("empty.png");
+	fs::write(&unknown, []).unwrap();
+
+	let sniffed = Icon::from_path_sniffing(mislabeled.clone(), 32, true).unwrap();
+	let empty = Icon::from_path_sniffing(unknown, 32, true).unwrap();
+	assert_eq!(sniffed.icon_type, IconType::Svg);
+	// Nothing to go by in the content, the extension decides
+	assert_eq!(empty.icon_type, IconType::Png);
+	assert_eq!(
+		Icon::from_path_sniffing(mislabeled, 32, false)
+			.unwrap()
+			.icon_type,
+		IconType::Png
+	);
+	// Rendered like any other SVG
+	let rendered =
+		get_png_from_svg_in(dir.path(), &sniffed.path, 32, &SvgOptions::default()).unwrap();
+	assert_eq!(
+		image::open(rendered).unwrap().to_rgba8().dimensions(),
+		(32, 32)
+	);
+}
+
 #[test]
 fn test_get_icon_path() {
+	let _env = crate::test_utils::lock_env();
 	// Create an instance of the DesktopFile struct with some dummy data
 	let desktop_file = DesktopFile {
 		path: PathBuf::new(),
@@ -337,7 +2037,7 @@ fn test_get_icon_path() {
 		command: None,
 		categories: vec![],
 		icon: Some("krita".into()),
-		no_display: false,
+		..Default::default()
 	};
 
 	// Call the get_icon_path() function with a size argument and store the result
@@ -354,7 +2054,29 @@ fn test_get_icon_path() {
 	));
 }
 
+lazy_static! {
+	static ref IMAGE_CACHE_DIR: PathBuf = {
+		let image_cache_dir = default_image_cache_dir();
+		match check_cache_dir_writable(&image_cache_dir) {
+			Ok(()) => image_cache_dir,
+			Err(err) => {
+				let temp_cache_dir = env::temp_dir().join("protostar_icon_cache");
+				warn!("{}, using {} instead", err, temp_cache_dir.display());
+				create_dir_all(&temp_cache_dir)
+					.expect("Could not create temporary image cache directory");
+				temp_cache_dir
+			}
+		}
+	};
+}
+
+/// Where rendered icons are cached, checked to be writable once so icons don't each fail to render.
+/// Falls back to a temporary directory if it isn't.
 pub fn get_image_cache_dir() -> PathBuf {
+	IMAGE_CACHE_DIR.clone()
+}
+
+fn default_image_cache_dir() -> PathBuf {
 	let cache_dir;
 	if let Ok(xdg_cache_home) = std::env::var("XDG_CACHE_HOME") {
 		cache_dir =
@@ -362,42 +2084,528 @@ pub fn get_image_cache_dir() -> PathBuf {
 	} else {
 		cache_dir = dirs::home_dir().unwrap().join(".cache");
 	}
-	let image_cache_dir = cache_dir.join("protostar_icon_cache");
-	create_dir_all(&image_cache_dir).expect("Could not create image cache directory");
-	return image_cache_dir;
+	return cache_dir.join("protostar_icon_cache");
+}
+
+/// Errors with what to do about it if icons can't be cached in `dir`
+pub fn check_cache_dir_writable(dir: &Path) -> Result<(), std::io::Error> {
+	let not_writable = |reason: String| {
+		std::io::Error::new(
+			ErrorKind::PermissionDenied,
+			format!(
+				"icon cache directory {} is not writable ({}), fix its ownership or permissions or set XDG_CACHE_HOME",
+				dir.display(),
+				reason
+			),
+		)
+	};
+	create_dir_all(dir).map_err(|e| not_writable(e.to_string()))?;
+	if fs::metadata(dir)?.permissions().readonly() {
+		return Err(not_writable("read-only".to_string()));
+	}
+	let probe = dir.join(".write_test");
+	File::create(&probe).map_err(|e| not_writable(e.to_string()))?;
+	let _ = fs::remove_file(probe);
+	Ok(())
+}
+
+#[test]
+fn test_check_cache_dir_writable() {
+	use std::os::unix::fs::PermissionsExt;
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let cache_dir = dir.path().join("protostar_icon_cache");
+	assert!(check_cache_dir_writable(&cache_dir).is_ok());
+
+	fs::set_permissions(&cache_dir, fs::Permissions::from_mode(0o555)).unwrap();
+	let err = check_cache_dir_writable(&cache_dir).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+	assert!(err.to_string().contains(&cache_dir.display().to_string()));
+	fs::set_permissions(&cache_dir, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Disk space taken in the icon cache by the PNGs rendered from `icon` (the value of an `Icon` key), in bytes
+pub fn icon_cache_size(icon: &str) -> u64 {
+	IconResolver::default().icon_cache_size(icon)
+}
+
+/// Total size of the PNGs in `cache_dir` rendered from `source`, in every size and color mode
+fn cached_size_in(cache_dir: &Path, source: &Path) -> u64 {
+	let Some(file_name) = source.file_name().and_then(|name| name.to_str()) else {
+		return 0;
+	};
+	let prefix = format!("{}-", file_name);
+	fs::read_dir(cache_dir)
+		.into_iter()
+		.flatten()
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| {
+			let name = entry.file_name();
+			let name = name.to_string_lossy();
+			name.starts_with(&prefix) && name.ends_with(".png")
+		})
+		.filter_map(|entry| entry.metadata().ok())
+		.filter(|metadata| metadata.is_file())
+		.map(|metadata| metadata.len())
+		.sum()
+}
+
+#[test]
+fn test_cached_size_in() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let cached = |name: &str, len: usize| fs::write(dir.path().join(name), vec![0; len]).unwrap();
+	cached("krita.svg-1234-64.png", 10);
+	cached("krita.svg-1234-128-mono-ffffffff.png", 20);
+	cached("krita.svg-1234-64-round250.png", 5);
+	cached("krita-extra.svg-99-64.png", 100);
+	cached("gimp.svg-55-64.png", 40);
+	cached("krita.svg-notes.txt", 1000);
+
+	let krita = Path::new("/usr/share/icons/hicolor/scalable/apps/krita.svg");
+	assert_eq!(cached_size_in(dir.path(), krita), 35);
+	assert_eq!(cached_size_in(dir.path(), Path::new("/icons/gimp.svg")), 40);
+	assert_eq!(
+		cached_size_in(dir.path(), Path::new("/icons/inkscape.svg")),
+		0
+	);
+	assert_eq!(cached_size_in(&dir.path().join("missing"), krita), 0);
+}
+
+pub fn get_png_from_svg(
+	svg_path: impl AsRef<Path>,
+	size: u16,
+	options: &SvgOptions,
+) -> Result<PathBuf, std::io::Error> {
+	get_png_from_svg_in(&get_image_cache_dir(), svg_path.as_ref(), size, options)
 }
 
-pub fn get_png_from_svg(svg_path: impl AsRef<Path>, size: u16) -> Result<PathBuf, std::io::Error> {
+fn get_png_from_svg_in(
+	cache_dir: &Path,
+	svg_path: &Path,
+	size: u16,
+	options: &SvgOptions,
+) -> Result<PathBuf, std::io::Error> {
+	let size = clamp_icon_size(size);
 	let svg_path = fs::canonicalize(svg_path)?;
 	let svg_data = fs::read(svg_path.as_path())?;
-	let tree = Tree::from_data(svg_data.as_slice(), &resvg::usvg::Options::default())
-		.map_err(|_| ErrorKind::InvalidData)?;
 
-	let png_path = get_image_cache_dir().join(format!(
-		"{}-{}.png",
+	let png_path = cache_dir.join(format!(
+		"{}-{}-{}{}.png",
 		svg_path.file_name().unwrap().to_str().unwrap(),
-		svg_data.len()
+		svg_data.len(),
+		size,
+		options.cache_suffix()
+	));
+
+	if png_path.exists() {
+		return Ok(png_path);
+	}
+
+	render_svg(&svg_data, size, &png_path, options)?;
+	Ok(png_path)
+}
+
+#[test]
+fn test_get_png_from_svg_dpi() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg_path = dir.path().join("test_cached_dpi.svg");
+	fs::write(
+		&svg_path,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10mm\" height=\"10mm\"><rect width=\"100%\" height=\"100%\" fill=\"#07c\"/></svg>",
+	)
+	.unwrap();
+	let cache_dir = dir.path().join("cache");
+	fs::create_dir_all(&cache_dir).unwrap();
+	let render = |dpi: f32| {
+		let options = SvgOptions {
+			dpi,
+			fixed_canvas: true,
+			..Default::default()
+		};
+		get_png_from_svg_in(&cache_dir, &svg_path, 128, &options).unwrap()
+	};
+
+	let low = render(96.0);
+	let high = render(150.0);
+	assert_ne!(low, high);
+	assert!(high.to_string_lossy().ends_with("-128-dpi150-fixed.png"));
+	// A render at another DPI doesn't come back from the cache
+	let drawn = |png_path: &Path| {
+		let image = image::open(png_path).unwrap().to_rgba8();
+		image.pixels().filter(|p| p[3] > 0).count()
+	};
+	assert!(drawn(&high) > drawn(&low));
+	assert_eq!(render(96.0), low);
+}
+
+/// Converts an image the backend can't load (eg: WebP) to a cached PNG
+pub fn get_png_from_raster(
+	image_path: impl AsRef<Path>,
+	size: u16,
+	options: &SvgOptions,
+) -> Result<PathBuf, std::io::Error> {
+	get_png_from_raster_in(&get_image_cache_dir(), image_path.as_ref(), size, options)
+}
+
+fn get_png_from_raster_in(
+	cache_dir: &Path,
+	image_path: &Path,
+	size: u16,
+	options: &SvgOptions,
+) -> Result<PathBuf, std::io::Error> {
+	let size = clamp_icon_size(size);
+	let image_path = fs::canonicalize(image_path)?;
+	let png_path = cache_dir.join(format!(
+		"{}-{}-{}{}.png",
+		image_path.file_name().unwrap().to_str().unwrap(),
+		fs::metadata(&image_path)?.len(),
+		size,
+		options.cache_suffix()
 	));
 
 	if png_path.exists() {
 		return Ok(png_path);
 	}
 
+	resize_raster(&image_path, size, &png_path, options)?;
+	Ok(png_path)
+}
+
+#[test]
+fn test_get_png_from_raster_monochrome() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let png_path = dir.path().join("colorful.png");
+	image::RgbaImage::from_pixel(8, 8, image::Rgba([230, 50, 60, 255]))
+		.save(&png_path)
+		.unwrap();
+	let cache_dir = dir.path().join("cache");
+	fs::create_dir_all(&cache_dir).unwrap();
+	let monochrome = SvgOptions {
+		color_mode: IconColorMode::Monochrome(Rgba::WHITE),
+		..Default::default()
+	};
+
+	let full_color =
+		get_png_from_raster_in(&cache_dir, &png_path, 8, &SvgOptions::default()).unwrap();
+	let gray = get_png_from_raster_in(&cache_dir, &png_path, 8, &monochrome).unwrap();
+	assert_ne!(full_color, gray);
+	assert!(gray.to_string_lossy().ends_with("-8-mono-ffffffff.png"));
+	let pixel = |path: &Path| image::open(path).unwrap().to_rgba8().get_pixel(4, 4).0;
+	assert_eq!(pixel(&full_color), [230, 50, 60, 255]);
+	let [r, g, b, _] = pixel(&gray);
+	assert!(r == g && g == b);
+}
+
+fn resize_raster(
+	image_path: &Path,
+	size: u16,
+	png_path: &Path,
+	options: &SvgOptions,
+) -> Result<(), std::io::Error> {
+	let mut image = image::open(image_path)
+		.map_err(|_| ErrorKind::InvalidData)?
+		.resize(
+			size.into(),
+			size.into(),
+			image::imageops::FilterType::Lanczos3,
+		);
+	if let IconColorMode::Monochrome(_) = options.color_mode {
+		image = image.grayscale();
+	}
+	if options.corner_radius > 0.0 {
+		let mut rgba = image.into_rgba8();
+		let (width, height) = rgba.dimensions();
+		mask_rounded_corners(&mut rgba, width, height, options.corner_radius, false);
+		image = rgba.into();
+	}
+	image
+		.save_with_format(png_path, image::ImageFormat::Png)
+		.map_err(|_| ErrorKind::InvalidData)?;
+	Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+	/// Resolution physical units (eg: `mm`) are resolved at
+	pub dpi: f32,
+	/// Draw the SVG at the size it declares on the `size`x`size` canvas instead of stretching it to fill it
+	pub fixed_canvas: bool,
+	/// Also applies to the raster images `rasterize_to_png_with_options` resizes
+	pub color_mode: IconColorMode,
+	/// Corner radius as a fraction of the size, see `IconResolver::corner_radius`, also applies to raster images
+	pub corner_radius: f32,
+}
+impl SvgOptions {
+	/// Keeps renders with different options apart in the icon cache
+	fn cache_suffix(&self) -> String {
+		let mut suffix = String::new();
+		if self.dpi != 96.0 {
+			suffix.push_str(&format!("-dpi{}", self.dpi));
+		}
+		if self.fixed_canvas {
+			suffix.push_str("-fixed");
+		}
+		suffix.push_str(&self.color_mode.cache_suffix());
+		suffix.push_str(&corner_radius_cache_suffix(self.corner_radius));
+		suffix
+	}
+}
+impl Default for SvgOptions {
+	fn default() -> Self {
+		SvgOptions {
+			dpi: 96.0,
+			fixed_canvas: false,
+			color_mode: IconColorMode::FullColor,
+			corner_radius: 0.0,
+		}
+	}
+}
+
+fn render_svg(
+	svg_data: &[u8],
+	size: u16,
+	png_path: &Path,
+	options: &SvgOptions,
+) -> Result<(), std::io::Error> {
+	let usvg_options = resvg::usvg::Options {
+		dpi: options.dpi.into(),
+		..Default::default()
+	};
+	let tree = Tree::from_data(svg_data, &usvg_options).map_err(|_| ErrorKind::InvalidData)?;
 	let mut pixmap = Pixmap::new(size.into(), size.into()).unwrap();
-	render(
-		&tree,
-		FitTo::Width(size.into()),
-		Transform::identity(),
-		pixmap.as_mut(),
-	);
+	let fit_to = if options.fixed_canvas {
+		FitTo::Original
+	} else {
+		FitTo::Width(size.into())
+	};
+	render(&tree, fit_to, Transform::identity(), pixmap.as_mut());
+	if let IconColorMode::Monochrome(color) = options.color_mode {
+		// Only the coverage of each pixel is kept, the pixels are premultiplied
+		for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+			let alpha = pixel[3] as f32 / 255.0 * color.a;
+			pixel[0] = (color.r * alpha * 255.0).round() as u8;
+			pixel[1] = (color.g * alpha * 255.0).round() as u8;
+			pixel[2] = (color.b * alpha * 255.0).round() as u8;
+			pixel[3] = (alpha * 255.0).round() as u8;
+		}
+	}
+	if options.corner_radius > 0.0 {
+		mask_rounded_corners(
+			pixmap.data_mut(),
+			size.into(),
+			size.into(),
+			options.corner_radius,
+			true,
+		);
+	}
 	pixmap
-		.save_png(&png_path)
+		.save_png(png_path)
 		.map_err(|_| ErrorKind::InvalidData)?;
+	Ok(())
+}
+
+/// Renders an SVG, or resizes a PNG or WebP, to a `size`x`size` PNG in `out_dir`.
+/// Unlike `get_png_from_svg` this never touches the icon cache or the icon themes.
+pub fn rasterize_to_png(
+	path: impl AsRef<Path>,
+	size: u16,
+	out_dir: impl AsRef<Path>,
+) -> Result<PathBuf, std::io::Error> {
+	rasterize_to_png_with_options(path, size, out_dir, &SvgOptions::default())
+}
+
+/// Same as `rasterize_to_png`, with control over how SVGs are rendered
+pub fn rasterize_to_png_with_options(
+	path: impl AsRef<Path>,
+	size: u16,
+	out_dir: impl AsRef<Path>,
+	svg_options: &SvgOptions,
+) -> Result<PathBuf, std::io::Error> {
+	let path = path.as_ref();
+	let size = clamp_icon_size(size);
+	let file_stem = path
+		.file_stem()
+		.ok_or(ErrorKind::InvalidInput)?
+		.to_string_lossy();
+	let png_path = out_dir.as_ref().join(format!("{}-{}.png", file_stem, size));
+
+	match path.extension().and_then(|ext| ext.to_str()) {
+		Some("svg") => render_svg(&fs::read(path)?, size, &png_path, svg_options)?,
+		Some("png") | Some("webp") => resize_raster(path, size, &png_path, svg_options)?,
+		_ => return Err(ErrorKind::InvalidInput.into()),
+	}
 	Ok(png_path)
 }
+
+#[test]
+fn test_rasterize_to_png() {
+	use image::GenericImageView;
+	let _env = crate::test_utils::lock_env();
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg_path = dir.path().join("test_rasterize.svg");
+	fs::write(
+		&svg_path,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><circle cx=\"5\" cy=\"5\" r=\"5\" fill=\"#e34\"/></svg>",
+	)
+	.unwrap();
+
+	let svg_png = rasterize_to_png(&svg_path, 64, dir.path()).unwrap();
+	assert!(svg_png.starts_with(dir.path()));
+	assert_eq!(image::open(&svg_png).unwrap().dimensions(), (64, 64));
+
+	// PNGs get resized too
+	let png_out = dir.path().join("resized");
+	fs::create_dir_all(&png_out).unwrap();
+	let resized = rasterize_to_png(&svg_png, 32, &png_out).unwrap();
+	assert_eq!(image::open(&resized).unwrap().dimensions(), (32, 32));
+
+	assert!(!get_image_cache_dir()
+		.join(svg_png.file_name().unwrap())
+		.exists());
+}
+#[test]
+fn test_rasterize_svg_dpi() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg_path = dir.path().join("test_dpi.svg");
+	fs::write(
+		&svg_path,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10mm\" height=\"10mm\"><rect width=\"100%\" height=\"100%\" fill=\"#07c\"/></svg>",
+	)
+	.unwrap();
+
+	// Width of the drawn part of the canvas
+	let extent_at = |dpi: f32| {
+		let out_dir = dir.path().join(dpi.to_string());
+		fs::create_dir_all(&out_dir).unwrap();
+		let options = SvgOptions {
+			dpi,
+			fixed_canvas: true,
+			..Default::default()
+		};
+		let png = rasterize_to_png_with_options(&svg_path, 128, &out_dir, &options).unwrap();
+		let image = image::open(png).unwrap().to_rgba8();
+		(0..image.width())
+			.filter(|x| image.get_pixel(*x, 0)[3] > 0)
+			.count()
+	};
+	// 10mm is about 38px at 96 DPI and 59px at 150 DPI
+	let low = extent_at(96.0);
+	let high = extent_at(150.0);
+	assert!((37..=39).contains(&low));
+	assert!(high > low);
+}
+
+#[test]
+fn test_rasterize_monochrome() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg_path = dir.path().join("colorful.svg");
+	fs::write(
+		&svg_path,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"5\" height=\"10\" fill=\"#e34\"/><circle cx=\"7\" cy=\"5\" r=\"3\" fill=\"#2a5\"/></svg>",
+	)
+	.unwrap();
+	let monochrome = SvgOptions {
+		color_mode: IconColorMode::Monochrome(Rgba::WHITE),
+		..Default::default()
+	};
+	let out_dir = |name: &str| {
+		let out_dir = dir.path().join(name);
+		fs::create_dir_all(&out_dir).unwrap();
+		out_dir
+	};
+
+	let full_color = rasterize_to_png(&svg_path, 32, out_dir("full")).unwrap();
+	let full_color = image::open(full_color).unwrap().to_rgba8();
+	assert!(full_color.pixels().any(|p| p[0] != p[1]));
+
+	let svg_png =
+		rasterize_to_png_with_options(&svg_path, 32, out_dir("svg"), &monochrome).unwrap();
+	let svg_image = image::open(svg_png).unwrap().to_rgba8();
+	assert!(svg_image.pixels().any(|p| p[3] == 255));
+	assert!(svg_image
+		.pixels()
+		.filter(|p| p[3] > 0)
+		.all(|p| p[0] == 255 && p[1] == 255 && p[2] == 255));
+
+	// Raster icons are desaturated instead
+	let raster_png = rasterize_to_png_with_options(
+		dir.path().join("full").join("colorful-32.png"),
+		32,
+		out_dir("raster"),
+		&monochrome,
+	)
+	.unwrap();
+	let raster_image = image::open(raster_png).unwrap().to_rgba8();
+	assert!(raster_image.pixels().all(|p| p[0] == p[1] && p[1] == p[2]));
+	assert_eq!(
+		IconColorMode::Monochrome(Rgba::WHITE).cache_suffix(),
+		"-mono-ffffffff"
+	);
+	assert_eq!(IconColorMode::FullColor.cache_suffix(), "");
+}
+
+#[test]
+fn test_rasterize_rounded_corners() {
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg_path = dir.path().join("square.svg");
+	fs::write(
+		&svg_path,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"10\" height=\"10\" fill=\"#2a5\"/></svg>",
+	)
+	.unwrap();
+	let rounded = SvgOptions {
+		corner_radius: 0.25,
+		..Default::default()
+	};
+	let assert_rounded = |png_path: PathBuf| {
+		let image = image::open(png_path).unwrap().to_rgba8();
+		for (x, y) in [(0, 0), (31, 0), (0, 31), (31, 31), (1, 1)] {
+			assert_eq!(image.get_pixel(x, y)[3], 0, "corner {},{}", x, y);
+		}
+		for (x, y) in [(16, 16), (16, 0), (0, 16), (31, 16), (4, 4)] {
+			assert_eq!(image.get_pixel(x, y)[3], 255, "inside {},{}", x, y);
+		}
+	};
+
+	let svg_png = rasterize_to_png_with_options(&svg_path, 32, dir.path(), &rounded).unwrap();
+	assert_rounded(svg_png);
+
+	// Raster icons get the same mask
+	let raster_out = dir.path().join("raster");
+	fs::create_dir_all(&raster_out).unwrap();
+	let square_png = rasterize_to_png(&svg_path, 32, &raster_out).unwrap();
+	let square = image::open(&square_png).unwrap().to_rgba8();
+	assert_eq!(square.get_pixel(0, 0)[3], 255);
+	let rounded_out = dir.path().join("rounded");
+	fs::create_dir_all(&rounded_out).unwrap();
+	let raster_png =
+		rasterize_to_png_with_options(&square_png, 32, &rounded_out, &rounded).unwrap();
+	assert_rounded(raster_png);
+
+	// Raster icons keep their aspect ratio, the corners are where the image ends
+	let wide_png = dir.path().join("wide.png");
+	image::RgbaImage::from_pixel(64, 32, image::Rgba([40, 170, 80, 255]))
+		.save(&wide_png)
+		.unwrap();
+	let wide_out = dir.path().join("wide");
+	fs::create_dir_all(&wide_out).unwrap();
+	let wide = rasterize_to_png_with_options(&wide_png, 32, &wide_out, &rounded).unwrap();
+	let wide = image::open(wide).unwrap().to_rgba8();
+	assert_eq!(wide.dimensions(), (32, 16));
+	for (x, y) in [(0, 0), (31, 0), (0, 15), (31, 15)] {
+		assert_eq!(wide.get_pixel(x, y)[3], 0, "corner {},{}", x, y);
+	}
+	for (x, y) in [(16, 0), (16, 15), (0, 8), (31, 8)] {
+		assert_eq!(wide.get_pixel(x, y)[3], 255, "edge {},{}", x, y);
+	}
+
+	assert_eq!(corner_radius_cache_suffix(0.25), "-round250");
+	assert_eq!(corner_radius_cache_suffix(0.0), "");
+}
+
 #[test]
 fn test_render_svg_to_png() {
 	use image::GenericImageView;
+	let _env = crate::test_utils::lock_env();
 	// Create temporary input and output paths
 	let svg_path = env::current_dir().unwrap().join("test_input.svg");
 
@@ -410,7 +2618,7 @@ fn test_render_svg_to_png() {
 	fs::write(&svg_path, test_svg_data).unwrap();
 
 	// Call the function with the test input and output paths and a size of 200
-	let png_path = get_png_from_svg(&svg_path, 200).unwrap();
+	let png_path = get_png_from_svg(&svg_path, 200, &SvgOptions::default()).unwrap();
 	dbg!(&png_path);
 
 	// Check that the output file exists
@@ -427,3 +2635,30 @@ fn test_render_svg_to_png() {
 	fs::remove_file(&svg_path).unwrap();
 	fs::remove_file(&png_path).unwrap();
 }
+
+#[test]
+fn test_render_svg_max_icon_size() {
+	use image::GenericImageView;
+	let _env = crate::test_utils::lock_env();
+	let dir = tempdir::TempDir::new("test").unwrap();
+	let svg_path = dir.path().join("test_max_size.svg");
+	fs::write(
+		&svg_path,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"10\" height=\"10\" fill=\"#07c\"/></svg>",
+	)
+	.unwrap();
+
+	// The maximum is for the whole process, put it back even if this fails
+	struct RestoreMaxIconSize(u16);
+	impl Drop for RestoreMaxIconSize {
+		fn drop(&mut self) {
+			set_max_icon_size(self.0);
+		}
+	}
+	let _restore = RestoreMaxIconSize(MAX_ICON_SIZE.load(Ordering::Relaxed));
+	set_max_icon_size(512);
+	let png_path =
+		get_png_from_svg_in(dir.path(), &svg_path, 4096, &SvgOptions::default()).unwrap();
+	let (width, height) = image::open(&png_path).unwrap().dimensions();
+	assert!(width <= 512 && height <= 512);
+}